@@ -0,0 +1,71 @@
+use super::*;
+
+/// Format Cara source, keeping its comments, (collapsed) blank lines and the way its
+/// literals are written
+pub fn format(code: &str, file: &str) -> Result<String, ParseError> {
+    let unit = parse(code.to_string(), file.to_string())?;
+
+    let mut printer = Printer::with_trivia(trivia(code)).with_source(code);
+    printer.print_comp_unit(&unit);
+
+    Ok(printer.finish())
+}
+
+/// The comments and blank lines of Cara source, keyed by byte offset
+pub fn trivia(code: &str) -> Vec<(usize, Trivia)> {
+    let bytes = code.as_bytes();
+    let mut trivia = Vec::new();
+    let mut line_start = 0;
+    // Nothing but whitespace seen on the current line so far
+    let mut line_blank = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'"', _) => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+                line_blank = false;
+            }
+            (b'/', Some(b'/')) | (b'/', Some(b'*')) => {
+                let end = if bytes[i + 1] == b'/' {
+                    code[i..].find('\n').map_or(code.len(), |end| i + end)
+                } else {
+                    code[i + 2..]
+                        .find("*/")
+                        .map_or(code.len(), |end| i + end + 4)
+                };
+                trivia.push((
+                    i,
+                    Trivia::Comment {
+                        text: code[i..end].trim_end().to_string(),
+                        trailing: !line_blank,
+                    },
+                ));
+                i = end;
+                line_blank = false;
+            }
+            (b'\n', _) => {
+                if line_blank {
+                    trivia.push((line_start, Trivia::BlankLine));
+                }
+                i += 1;
+                line_start = i;
+                line_blank = true;
+            }
+            (b' ' | b'\t' | b'\r', _) => i += 1,
+            _ => {
+                i += 1;
+                line_blank = false;
+            }
+        }
+    }
+
+    trivia
+}
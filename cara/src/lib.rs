@@ -0,0 +1,12 @@
+//! The reference front end of Cara: a parser producing the rasta AST and a
+//! source formatter built on the rasta printer.
+
+#![allow(clippy::result_large_err)]
+
+mod fmt;
+mod parser;
+
+pub use fmt::*;
+pub use parser::*;
+
+use rasta::*;
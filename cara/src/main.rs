@@ -0,0 +1,76 @@
+use std::env::args;
+use std::fs;
+use std::io::{stderr, IsTerminal};
+use std::process::exit;
+
+use rasta::DiagnosticFormat;
+
+fn usage(exe: &str) -> ! {
+    eprintln!("Usage: {} fmt [--check] <source_file>...", exe);
+    exit(2);
+}
+
+fn main() {
+    let mut args = args();
+    let exe = args.next().unwrap();
+
+    match args.next().as_deref() {
+        Some("fmt") => {}
+        _ => usage(&exe),
+    }
+
+    let mut check = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        usage(&exe);
+    }
+
+    // Colors only make sense on a terminal, not in a log or a pipe
+    let format = if stderr().is_terminal() {
+        DiagnosticFormat::Colored
+    } else {
+        DiagnosticFormat::Plain
+    };
+
+    let mut failed = false;
+    for path in files {
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("error: cannot read {}: {}", path, err);
+                failed = true;
+                continue;
+            }
+        };
+
+        let formatted = match cara::format(&source, &path) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprint!("{}", err.diagnostic.render(&err.source_map, format));
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{}", path);
+            failed = true;
+        } else if let Err(err) = fs::write(&path, formatted) {
+            eprintln!("error: cannot write {}: {}", path, err);
+            failed = true;
+        }
+    }
+
+    if failed {
+        exit(1);
+    }
+}
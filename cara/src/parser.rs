@@ -0,0 +1,761 @@
+use std::fmt;
+
+use pest::{
+    error::InputLocation,
+    iterators::{Pair, Pairs},
+    pratt_parser::{Assoc, Op, PrattParser},
+    Parser,
+};
+use pest_derive::Parser;
+
+use super::*;
+
+#[derive(Parser)]
+#[grammar = "cara.pest"]
+pub struct CaraParser;
+
+/// A syntax error, or Cara code the rasta AST cannot hold
+#[derive(Debug)]
+pub struct ParseError {
+    pub diagnostic: Diagnostic,
+    /// The source map holding the file that failed, to render the diagnostic with
+    pub source_map: SourceMap,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.diagnostic.render(&self.source_map, DiagnosticFormat::Plain))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(code: String, file: String) -> Result<CompUnit, ParseError> {
+    let mut source_map = SourceMap::new();
+    let file = source_map.add_file(file, code.clone());
+
+    let root = match CaraParser::parse(Rule::comp_unit, &code) {
+        Ok(mut pairs) => pairs.next().unwrap(),
+        Err(err) => {
+            let bytes = match err.location {
+                InputLocation::Pos(pos) => pos..pos,
+                InputLocation::Span((start, end)) => start..end,
+            };
+            let diagnostic = Diagnostic::error("syntax error")
+                .with_primary(Span::new(file, bytes), err.variant.message());
+            return Err(ParseError {
+                diagnostic,
+                source_map,
+            });
+        }
+    };
+
+    if let Some(diagnostic) = check_supported(file, &root) {
+        return Err(ParseError {
+            diagnostic,
+            source_map,
+        });
+    }
+
+    Ok(to_ast(file, root, source_map))
+}
+
+/// Reject the constructs the grammar accepts but the AST has no node for
+fn check_supported(file: FileId, root: &Pair<Rule>) -> Option<Diagnostic> {
+    let top_calls: Vec<usize> = root
+        .clone()
+        .into_inner()
+        .filter(|item| item.as_rule() == Rule::builtin_function_call)
+        .map(|item| item.as_span().start())
+        .collect();
+
+    root.clone().into_inner().flatten().find_map(|pair| {
+        let what = match pair.as_rule() {
+            Rule::new_class => "class instances",
+            Rule::builtin_function_call if !top_calls.contains(&pair.as_span().start()) => {
+                "builtin function calls inside expressions"
+            }
+            _ => return None,
+        };
+        Some(
+            Diagnostic::error(format!("{} are not supported yet", what))
+                .with_primary(get_span(file, pair.as_span()), "cannot be represented in the AST"),
+        )
+    })
+}
+
+fn get_span(file: FileId, span: pest::Span<'_>) -> Span {
+    Span::new(file, span.start()..span.end())
+}
+
+fn to_ast(file: FileId, rules: Pair<Rule>, source_map: SourceMap) -> CompUnit {
+    let mut items = Vec::new();
+    let span = rules.as_span();
+
+    for line in rules.into_inner() {
+        match line.as_rule() {
+            Rule::const_decl => items.push(GlobalItem::ConstDecl(parse_const_decl(file, line))),
+            Rule::inline_asm => items.push(GlobalItem::InlineAsm(parse_inline_asm(file, line))),
+            Rule::builtin_function_call => {
+                items.push(GlobalItem::BuiltinFnCall(parse_builtin_function_call(file, line)))
+            }
+            Rule::soi | Rule::eoi => {}
+            _ => unimplemented!(),
+        }
+    }
+
+    CompUnit {
+        global_items: items,
+        span: get_span(file, span),
+        source_map,
+    }
+}
+
+fn parse_const_decl(file: FileId, rules: Pair<Rule>) -> ConstDecl {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let attributes = rules_iter.next().unwrap();
+
+    let (id, attr) = if let Rule::attributes = attributes.as_rule() {
+        (
+            rules_iter.next().unwrap(),
+            Some(parse_attributes(file, attributes)),
+        )
+    } else {
+        (attributes, None)
+    };
+    let id = parse_ident(id);
+
+    let init = parse_const_init_val(file, rules_iter.next().unwrap());
+
+    ConstDecl {
+        span: get_span(file, rules.as_span()),
+        id,
+        attr,
+        init,
+    }
+}
+
+fn parse_const_init_val(file: FileId, rules: Pair<Rule>) -> ConstInitVal {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let init_val = rules_iter.next().unwrap();
+
+    match init_val.as_rule() {
+        Rule::const_exp => ConstInitVal::Exp(parse_const_expr(file, init_val)),
+        Rule::func_def => ConstInitVal::Function(parse_function_def(file, init_val)),
+        Rule::proto_def => ConstInitVal::Proto(parse_proto_def(file, init_val)),
+        Rule::class_def => ConstInitVal::Class(parse_class_def(file, init_val)),
+        _ => unimplemented!(),
+    }
+}
+
+fn parse_builtin_function_call(file: FileId, rules: Pair<Rule>) -> BuiltinFunctionCall {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let func = match rules_iter.next().unwrap().as_str() {
+        "import" => BuiltinFunction::Import,
+        "do_magic" => BuiltinFunction::DoMagic,
+        "module" => BuiltinFunction::Module,
+        "first_module" => BuiltinFunction::FirstModule,
+        func => panic!("Unknown builtin function {}!", func),
+    };
+
+    BuiltinFunctionCall {
+        func,
+        args: rules_iter.map(|rules| parse_expr(file, rules)).collect(),
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+fn parse_class_def(file: FileId, rules: Pair<Rule>) -> ClassDef {
+    let mut members = Vec::new();
+    let mut consts = Vec::new();
+
+    for rule in rules.clone().into_inner() {
+        match rule.as_rule() {
+            Rule::class_member => {
+                let mut rule_iter = rule.clone().into_inner();
+                let id = parse_ident(rule_iter.next().unwrap());
+                let ty = parse_vtype(file, rule_iter.next().unwrap());
+                members.push(ClassMember {
+                    ty,
+                    id,
+                    span: get_span(file, rule.as_span()),
+                });
+            }
+            Rule::const_decl => consts.push(parse_const_decl(file, rule)),
+            _ => unimplemented!(),
+        }
+    }
+
+    ClassDef {
+        members,
+        consts,
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+fn parse_const_expr(file: FileId, rules: Pair<Rule>) -> ConstExp {
+    ConstExp {
+        exp: parse_expr(file, rules),
+    }
+}
+
+fn parse_attributes(file: FileId, rules: Pair<Rule>) -> Attributes {
+    Attributes {
+        span: get_span(file, rules.as_span()),
+        attrs: rules
+            .into_inner()
+            .map(|attr| attr.as_str().into())
+            .collect(),
+    }
+}
+
+fn parse_ident(rules: Pair<Rule>) -> String {
+    rules.as_str().to_string()
+}
+
+fn parse_deref(file: FileId, rules: Pair<Rule>) -> Deref {
+    let mut primary_iter = rules.clone().into_inner();
+
+    let tmp = primary_iter.next().unwrap();
+
+    match tmp.as_rule() {
+        Rule::lval => {
+            let lval = parse_lval(file, tmp);
+            if let Some(exp) = primary_iter.next() {
+                let exp = parse_expr(file, exp);
+                Deref::DerefPtr(lval, exp, get_span(file, rules.as_span()))
+            } else {
+                Deref::DerefId(lval, get_span(file, rules.as_span()))
+            }
+        }
+        Rule::exp => {
+            let exp = parse_expr(file, tmp);
+            if let Some(exp2) = primary_iter.next() {
+                let exp2 = parse_expr(file, exp2);
+                Deref::DerefPtrExp(exp, exp2, get_span(file, rules.as_span()))
+            } else {
+                Deref::DerefExp(exp, get_span(file, rules.as_span()))
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn parse_values(file: FileId, rules: Pair<Rule>) -> Exp {
+    match rules.as_rule() {
+        Rule::exp => parse_expr(file, rules),
+        Rule::string => Exp::Str(parse_string(rules.clone()), get_span(file, rules.as_span())),
+        Rule::array_def => parse_array_def(file, rules),
+        _ => unimplemented!(),
+    }
+}
+
+fn parse_array_def(file: FileId, rules: Pair<Rule>) -> Exp {
+    let mut rules_iter = rules.clone().into_inner();
+
+    if let Some(exp) = rules_iter.next() {
+        let value = parse_values(file, exp);
+        if let Some(tmp) = rules_iter.next() {
+            match tmp.as_rule() {
+                Rule::const_exp => {
+                    let num = parse_const_expr(file, tmp);
+                    Exp::Array(Box::new(Array::Template(
+                        value,
+                        num,
+                        get_span(file, rules.as_span()),
+                    )))
+                }
+                _ => {
+                    let mut values = vec![value, parse_values(file, tmp)];
+                    for tmp in rules_iter {
+                        values.push(parse_values(file, tmp));
+                    }
+                    Exp::Array(Box::new(Array::List(values, get_span(file, rules.as_span()))))
+                }
+            }
+        } else {
+            Exp::Array(Box::new(Array::List(
+                vec![value],
+                get_span(file, rules.as_span()),
+            )))
+        }
+    } else {
+        Exp::Array(Box::new(Array::List(Vec::new(), get_span(file, rules.as_span()))))
+    }
+}
+
+fn parse_expr(file: FileId, rules: Pair<Rule>) -> Exp {
+    let pratt = PrattParser::new()
+        .op(Op::infix(Rule::or, Assoc::Left))
+        .op(Op::infix(Rule::and, Assoc::Left))
+        .op(Op::infix(Rule::eq, Assoc::Left)
+            | Op::infix(Rule::neq, Assoc::Left)
+            | Op::infix(Rule::lt, Assoc::Left)
+            | Op::infix(Rule::gt, Assoc::Left)
+            | Op::infix(Rule::le, Assoc::Left)
+            | Op::infix(Rule::ge, Assoc::Left))
+        .op(Op::infix(Rule::bit_or, Assoc::Left))
+        .op(Op::infix(Rule::bit_xor, Assoc::Left))
+        .op(Op::infix(Rule::bit_and, Assoc::Left))
+        .op(Op::infix(Rule::shl, Assoc::Left) | Op::infix(Rule::shr, Assoc::Left))
+        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+        .op(Op::infix(Rule::mul, Assoc::Left)
+            | Op::infix(Rule::div, Assoc::Left)
+            | Op::infix(Rule::r#mod, Assoc::Left))
+        .op(Op::prefix(Rule::neg)
+            | Op::prefix(Rule::pos)
+            | Op::prefix(Rule::not)
+            | Op::prefix(Rule::bit_not));
+
+    pratt
+        .map_primary(|primary| match primary.as_rule() {
+            Rule::exp => parse_expr(file, primary),
+            Rule::number => parse_number(file, primary),
+            Rule::lval => Exp::LVal(Box::new(parse_lval(file, primary))),
+            Rule::func_call => Exp::FuncCall({
+                let mut primary_iter = primary.clone().into_inner();
+
+                let ids = vec![parse_ident(primary_iter.next().unwrap())];
+
+                let mut args = Vec::new();
+
+                for arg in primary_iter {
+                    args.push(parse_values(file, arg));
+                }
+
+                FuncCall {
+                    ids,
+                    args,
+                    span: get_span(file, primary.as_span()),
+                }
+            }),
+            Rule::get_addr => {
+                let mut primary_iter = primary.clone().into_inner();
+
+                let lval = parse_lval(file, primary_iter.next().unwrap());
+
+                Exp::GetAddr(Box::new(GetAddr {
+                    lval,
+                    span: get_span(file, primary.as_span()),
+                }))
+            }
+            Rule::deref => Exp::Deref(Box::new(parse_deref(file, primary.clone()))),
+
+            _ => panic!("Unkown primary {}!", primary),
+        })
+        .map_prefix(|op, rhs| {
+            let span = get_span(file, op.as_span());
+            match op.as_rule() {
+                Rule::neg => Exp::Unary(UnaryOp::Negative, Box::new(rhs), span),
+                Rule::pos => Exp::Unary(UnaryOp::Positive, Box::new(rhs), span),
+                Rule::not => Exp::Unary(UnaryOp::Not, Box::new(rhs), span),
+                Rule::bit_not => Exp::Unary(UnaryOp::BitNot, Box::new(rhs), span),
+                _ => unimplemented!(),
+            }
+        })
+        .map_postfix(|_lhs, _op| unimplemented!())
+        .map_infix(|lhs, op, rhs| {
+            let lhs = Box::new(lhs);
+            let rhs = Box::new(rhs);
+
+            Exp::Binary(
+                lhs,
+                match op.as_rule() {
+                    Rule::eq => BinaryOp::Eq,
+                    Rule::neq => BinaryOp::Neq,
+                    Rule::add => BinaryOp::Add,
+                    Rule::sub => BinaryOp::Sub,
+                    Rule::mul => BinaryOp::Mul,
+                    Rule::div => BinaryOp::Div,
+                    Rule::r#mod => BinaryOp::Mod,
+                    Rule::lt => BinaryOp::Lt,
+                    Rule::gt => BinaryOp::Gt,
+                    Rule::le => BinaryOp::Le,
+                    Rule::ge => BinaryOp::Ge,
+                    Rule::bit_and => BinaryOp::BitAnd,
+                    Rule::bit_or => BinaryOp::BitOr,
+                    Rule::bit_xor => BinaryOp::BitXor,
+                    Rule::shl => BinaryOp::Shl,
+                    Rule::shr => BinaryOp::Shr,
+                    Rule::and => BinaryOp::And,
+                    Rule::or => BinaryOp::Or,
+                    _ => unimplemented!(),
+                },
+                rhs,
+                get_span(file, op.as_span()),
+            )
+        })
+        .parse(rules.into_inner())
+}
+
+fn parse_param(file: FileId, rules: Pair<Rule>) -> Param {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let id = parse_ident(rules_iter.next().unwrap());
+    let ty = parse_vtype(file, rules_iter.next().unwrap());
+
+    Param {
+        ty,
+        id,
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+/// The parameters and the return type shared by function and proto definitions
+fn parse_signature(file: FileId, rules_iter: &mut Pairs<Rule>) -> (Vec<Param>, VType) {
+    let mut params = Vec::new();
+
+    loop {
+        let tmp = rules_iter.next().unwrap();
+        match tmp.as_rule() {
+            Rule::param => params.push(parse_param(file, tmp)),
+            _ => break (params, parse_vtype(file, tmp)),
+        }
+    }
+}
+
+/// A decimal literal with a fraction or an exponent is a float
+fn parse_number(file: FileId, rules: Pair<Rule>) -> Exp {
+    let text = rules.as_str().replace('_', "");
+    let span = get_span(file, rules.as_span());
+
+    let (radix, digits) = match text.get(..2) {
+        Some("0x") => (Radix::Hex, &text[2..]),
+        Some("0o") => (Radix::Octal, &text[2..]),
+        Some("0b") => (Radix::Binary, &text[2..]),
+        _ if text.contains(['.', 'e', 'E']) => {
+            return Exp::Float(Float {
+                num: text.parse().unwrap(),
+                span,
+            })
+        }
+        _ => (Radix::Decimal, &text[..]),
+    };
+
+    let (digits, suffix) = match digits.find(['u', 'i']) {
+        Some(start) => (&digits[..start], Some(parse_vtype_enum(&digits[start..]))),
+        None => (digits, None),
+    };
+    Exp::Number(Number {
+        num: BigInt::parse_bytes(digits.as_bytes(), radix.value()).unwrap(),
+        suffix,
+        radix,
+        span,
+    })
+}
+
+fn parse_function_def(file: FileId, rules: Pair<Rule>) -> FuncDef {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let (params, return_type) = parse_signature(file, &mut rules_iter);
+
+    FuncDef {
+        span: get_span(file, rules.as_span()),
+        params,
+        func_type: return_type,
+        block: parse_block(file, rules_iter.next().unwrap()),
+    }
+}
+
+fn parse_proto_def(file: FileId, rules: Pair<Rule>) -> ProtoDef {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let (params, return_type) = parse_signature(file, &mut rules_iter);
+
+    ProtoDef {
+        span: get_span(file, rules.as_span()),
+        params,
+        func_type: return_type,
+    }
+}
+
+fn parse_block(file: FileId, rules: Pair<Rule>) -> Block {
+    let rules_iter = rules.clone().into_inner();
+    let mut item = Vec::new();
+    for rule in rules_iter {
+        match rule.as_rule() {
+            Rule::stmt => item.push(BlockItem::Stmt(parse_stmt(file, rule))),
+            Rule::decl => item.push(BlockItem::Decl(parse_decl(file, rule))),
+            _ => unimplemented!(),
+        }
+    }
+    Block {
+        span: get_span(file, rules.as_span()),
+        items: item,
+    }
+}
+
+fn parse_decl(file: FileId, rules: Pair<Rule>) -> Decl {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let decl = rules_iter.next().unwrap();
+
+    match decl.as_rule() {
+        Rule::const_decl => Decl::Const(parse_const_decl(file, decl)),
+        Rule::var_decl => Decl::Var(parse_var_decl(file, decl)),
+        _ => unimplemented!(),
+    }
+}
+
+fn parse_var_decl(file: FileId, rules: Pair<Rule>) -> VarDecl {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let id = rules_iter.next().unwrap().as_str().to_string();
+    let ty = parse_vtype(file, rules_iter.next().unwrap());
+    let init = parse_init_val(file, rules_iter.next().unwrap());
+
+    VarDecl {
+        span: get_span(file, rules.as_span()),
+        id: id.as_str().to_string(),
+        ty,
+        init,
+    }
+}
+
+fn parse_init_val(file: FileId, rules: Pair<Rule>) -> InitVal {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let init_val = rules_iter.next().unwrap();
+
+    InitVal {
+        exp: parse_values(file, init_val),
+    }
+}
+
+fn parse_stmt(file: FileId, rules: Pair<Rule>) -> Stmt {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let Some(stmt) = rules_iter.next() else {
+        return Stmt::Exp(None);
+    };
+
+    match stmt.as_rule() {
+        Rule::r#return => Stmt::Return(parse_return(file, stmt)),
+        Rule::assign => Stmt::Assign(parse_assign(file, stmt)),
+        Rule::block => Stmt::Block(parse_block(file, stmt)),
+        Rule::r#if => Stmt::If(parse_if(file, stmt)),
+        Rule::r#while => Stmt::While(parse_while(file, stmt)),
+        Rule::inline_asm => Stmt::InlineAsm(parse_inline_asm(file, stmt)),
+        Rule::terminator => Stmt::Terminator(parse_terminator(file, stmt)),
+        Rule::r#for => Stmt::For(parse_for(file, stmt)),
+        Rule::exp => Stmt::Exp(Some(parse_expr(file, stmt))),
+        _ => unimplemented!(),
+    }
+}
+
+fn parse_inline_asm(file: FileId, rules: Pair<Rule>) -> InlineAsm {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let code = parse_string(rules_iter.next().unwrap());
+
+    let constraints = rules_iter.map(|rules| parse_asm_constraint(file, rules)).collect();
+
+    InlineAsm {
+        asm: code,
+        constraints,
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+fn parse_asm_constraint(file: FileId, rules: Pair<Rule>) -> AsmConstraint {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let id = parse_ident(rules_iter.next().unwrap());
+    let any = rules_iter.next().unwrap();
+
+    match any.as_rule() {
+        Rule::exp => AsmConstraint::In(id, parse_expr(file, any), get_span(file, rules.as_span())),
+        Rule::lval => {
+            let mut lval = parse_lval(file, any);
+            // `out a = x[i]` writes to an element
+            if let Some(index) = rules_iter.next() {
+                lval.exp = Some(parse_expr(file, index));
+            }
+            AsmConstraint::Out(id, lval, get_span(file, rules.as_span()))
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn parse_string(rules: Pair<Rule>) -> String {
+    let inner = rules.clone().into_inner().next().unwrap();
+    let mut string = String::new();
+    let mut chars = inner.as_str().chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        match chars.next().unwrap() {
+            'b' => string.push('\u{8}'),
+            'f' => string.push('\u{c}'),
+            'n' => string.push('\n'),
+            'r' => string.push('\r'),
+            't' => string.push('\t'),
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let code = u32::from_str_radix(&hex, 16).unwrap();
+                string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            // `"`, `\` and `/` stand for themselves
+            c => string.push(c),
+        }
+    }
+
+    string
+}
+
+fn parse_while(file: FileId, rules: Pair<Rule>) -> While {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let cond = parse_expr(file, rules_iter.next().unwrap());
+    let then = parse_block(file, rules_iter.next().unwrap());
+
+    While {
+        cond,
+        then,
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+fn parse_if(file: FileId, rules: Pair<Rule>) -> If {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let cond = rules_iter.next().unwrap();
+
+    let cond = parse_expr(file, cond);
+
+    let then = parse_block(file, rules_iter.next().unwrap());
+
+    let else_then = rules_iter.next().map(|rules| parse_block(file, rules));
+
+    If {
+        cond,
+        then,
+        else_then,
+        span: get_span(file, rules.as_span()),
+    }
+}
+
+fn parse_terminator(file: FileId, rules: Pair<Rule>) -> Terminator {
+    match rules.as_str() {
+        s if s.starts_with("break") => Terminator::Break(get_span(file, rules.as_span())),
+        s if s.starts_with("continue") => Terminator::Continue(get_span(file, rules.as_span())),
+        _ => panic!("Unknown terminator {}!", rules.as_str()),
+    }
+}
+
+fn parse_for(file: FileId, rules: Pair<Rule>) -> For {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let var_name = rules_iter.next().unwrap().as_str().to_string();
+    let start = parse_expr(file, rules_iter.next().unwrap());
+    let end = parse_expr(file, rules_iter.next().unwrap());
+    let step = parse_expr(file, rules_iter.next().unwrap());
+    let block = parse_block(file, rules_iter.next().unwrap());
+
+    For {
+        span: get_span(file, rules.as_span()),
+        var: var_name,
+        start,
+        end,
+        step,
+        then: block,
+    }
+}
+
+fn parse_assign(file: FileId, rules: Pair<Rule>) -> Assign {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let tmp = rules_iter.next().unwrap();
+
+    match tmp.as_rule() {
+        Rule::deref => {
+            let lhs = parse_deref(file, tmp);
+            let rhs = parse_expr(file, rules_iter.next().unwrap());
+            Assign::WritePtr(lhs, rhs, get_span(file, rules.as_span()))
+        }
+        Rule::lval => {
+            let id = parse_lval(file, tmp);
+            let exp = parse_expr(file, rules_iter.next().unwrap());
+
+            Assign::WriteVar(id, exp, get_span(file, rules.as_span()))
+        }
+        _ => panic!("Unknown assignment {}!",tmp),
+    }
+}
+
+fn parse_lval(file: FileId, rules: Pair<Rule>) -> LVal {
+    let ident = rules.as_str().to_string();
+
+    LVal {
+        ids: vec![ident],
+        span: get_span(file, rules.as_span()),
+        exp: None,
+    }
+}
+
+fn parse_return(file: FileId, rules: Pair<Rule>) -> Return {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let exp = rules_iter.next().map(|rules| parse_expr(file, rules));
+
+    Return {
+        span: get_span(file, rules.as_span()),
+        exp,
+    }
+}
+
+/// `u<bits>` and `i<bits>` are integers of any width, other names are classes
+fn parse_vtype_enum(name: &str) -> VTypeEnum {
+    let bits = |digits: &str| {
+        digits
+            .parse::<u32>()
+            .ok()
+            .filter(|_| !digits.starts_with(['0', '+']))
+    };
+
+    if let Some(bits) = name.strip_prefix('u').and_then(bits) {
+        return VTypeEnum::int(false, bits);
+    }
+    if let Some(bits) = name.strip_prefix('i').and_then(bits) {
+        return VTypeEnum::int(true, bits);
+    }
+    match name {
+        "void" => VTypeEnum::Void,
+        "bool" => VTypeEnum::Bool,
+        "f32" => VTypeEnum::F32,
+        "f64" => VTypeEnum::F64,
+        _ => VTypeEnum::Others(name.to_string()),
+    }
+}
+
+fn parse_vtype(file: FileId, rules: Pair<Rule>) -> VType {
+    let mut rules_iter = rules.clone().into_inner();
+
+    let vtype_enum = rules_iter.next().unwrap();
+
+    let vty_enum = parse_vtype_enum(vtype_enum.as_str());
+
+    if rules_iter.next().is_some() {
+        let mut star_cnt = 1usize;
+        for _ in rules_iter {
+            star_cnt += 1;
+        }
+        VType {
+            ty: vty_enum,
+            star: star_cnt,
+            span: get_span(file, rules.as_span()),
+        }
+    } else {
+        VType {
+            ty: vty_enum,
+            star: 0,
+            span: get_span(file, rules.as_span()),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use cara::parse;
+use rasta::{resolve, type_check, Diagnostic, NodeIds};
+
+/// Resolve and type check `source`, returning the diagnostics
+fn check(source: &str) -> Vec<Diagnostic> {
+    let unit = parse(source.to_string(), "test.cara".to_string()).unwrap();
+    let ids = NodeIds::new(&unit);
+    let resolution = resolve(&unit, &ids);
+    assert!(resolution.is_ok(), "{:?}", resolution.diagnostics);
+    type_check(&unit, &ids, &resolution).diagnostics
+}
+
+/// The messages of the diagnostics of `source`
+fn errors(source: &str) -> Vec<String> {
+    check(source)
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+/// `body` inside a function taking `n: u32`
+fn in_function(body: &str) -> String {
+    format!("const f = fn (arg n: u32) -> void {{ {} }};", body)
+}
+
+#[test]
+fn literal_loop_bounds_take_the_type_of_the_others() {
+    for bounds in ["0, n, 1", "n, 0, 1", "0, 10, n"] {
+        let source = in_function(&format!(
+            "for i in ({}) {{ var x: u32 = i; }}",
+            bounds
+        ));
+        assert_eq!(errors(&source), Vec::<String>::new(), "{}", bounds);
+    }
+}
+
+#[test]
+fn literal_loop_bounds_are_u64() {
+    let source = in_function("for i in (0, 10, 1) { var x: u64 = i; }");
+    assert_eq!(errors(&source), Vec::<String>::new());
+}
+
+#[test]
+fn loop_bounds_of_different_types() {
+    let source = in_function("var m: u8 = 1; for i in (0, n, m) { }");
+    assert_eq!(errors(&source), ["mismatched types"]);
+}
+
+#[test]
+fn literal_loop_bound_out_of_range() {
+    let source = in_function("var m: u8 = 1; for i in (0, 256, m) { }");
+    assert_eq!(errors(&source), ["literal `256` does not fit in `u8`"]);
+}
+
+#[test]
+fn smallest_signed_literal_needs_a_minus() {
+    let fits = [
+        "var x: i8 = -128;",
+        "var x: i8 = -128i8;",
+        "var x: i8 = 127i8;",
+        "var x: u8 = 255;",
+        "var x: i64 = -9223372036854775808;",
+        "var x: i1 = -1;",
+        "var x: u8 = -0;",
+        "var x: u4000000000 = 0u4000000000;",
+    ];
+    for body in fits {
+        assert_eq!(errors(&in_function(body)), Vec::<String>::new(), "{}", body);
+    }
+
+    let overflows = [
+        ("var x: i8 = 128;", "literal `128` does not fit in `i8`"),
+        ("var x: i8 = 128i8;", "literal `128` does not fit in `i8`"),
+        ("var x: i8 = -129;", "literal `129` does not fit in `i8`"),
+        ("var x: u8 = 256;", "literal `256` does not fit in `u8`"),
+        (
+            "var x: i64 = 9223372036854775808;",
+            "literal `9223372036854775808` does not fit in `i64`",
+        ),
+        ("var x: i1 = 1;", "literal `1` does not fit in `i1`"),
+    ];
+    for (body, error) in overflows {
+        assert_eq!(errors(&in_function(body)), [error], "{}", body);
+    }
+}
+
+#[test]
+fn negative_literal_of_an_unsigned_type() {
+    let source = in_function("var x: u8 = -1;");
+    assert_eq!(errors(&source), ["literal `1` does not fit in `u8`"]);
+}
@@ -0,0 +1,34 @@
+use cara::format;
+
+#[test]
+fn keeps_literals() {
+    let source = "const a = 1.5e3 + 0xff + 1_000u16 + 0b1010 + 2.;\n";
+    assert_eq!(format(source, "test.cara").unwrap(), source);
+}
+
+#[test]
+fn keeps_comments() {
+    let source = "// The answer\nconst a = 42; // Always\n\nconst b = a;\n";
+    assert_eq!(format(source, "test.cara").unwrap(), source);
+}
+
+#[test]
+fn keeps_inline_comments_in_place() {
+    let source = "\
+const a = 1 + /* two */ 2 * /* three */ 3;
+
+const f = fn (arg x: u8) -> u8 {
+    var y: u8 = g(x, /* default */ 0);
+    return /* unchanged */ y; // Done
+};
+";
+    assert_eq!(format(source, "test.cara").unwrap(), source);
+}
+
+#[test]
+fn inline_comments_move_before_the_next_operand() {
+    let source = "const a = 1 /* one */ + 2;\n";
+    let formatted = format(source, "test.cara").unwrap();
+    assert_eq!(formatted, "const a = 1 + /* one */ 2;\n");
+    assert_eq!(format(&formatted, "test.cara").unwrap(), formatted);
+}
@@ -0,0 +1,111 @@
+use cara::parse;
+use rasta::{unparse, Exp, Visitor};
+
+/// Print `source` and check the printed source parses back to the same AST
+fn roundtrip(source: &str) -> String {
+    let printed = unparse(&parse(source.to_string(), "test.cara".to_string()).unwrap());
+    let reparsed = parse(printed.clone(), "printed.cara".to_string()).unwrap();
+    assert_eq!(unparse(&reparsed), printed);
+    printed
+}
+
+#[test]
+fn items() {
+    roundtrip(
+        "
+        #[top, inline]
+        const add = fn (arg a: u8, arg b: u8) -> u8 { return a + b; };
+        const putc = fn (arg c: i8) -> void;
+        const Point = class { x: i32, y: i32, const origin = 0; };
+        const limit = 0xFF + 0b1010 * 7u16 - 1.5e3;
+        ",
+    );
+}
+
+#[test]
+fn statements() {
+    roundtrip(
+        "
+        const main = fn (arg p: u64*) -> u64 {
+            var xs: u64* = [1, 2, 3];
+            var ys: u64* = [0; 4];
+            var s: i8* = \"hi\\n\";
+            for i in (0, 3, 1) {
+                if xs[i] > 1 { break; } else { continue; }
+            }
+            while *p != 0 { *p = *p - 1; }
+            (p)[1] = *(p + 1);
+            p = &xs;
+            return (1 + 2) * 3 << 1 & 7 | ~1 ^ -2;
+        };
+        ",
+    );
+}
+
+#[test]
+fn builtin_function_calls() {
+    let printed = roundtrip("[import](std) [module](a, b) [first_module]() [do_magic](1)");
+    assert!(printed.contains("[import](std)"));
+}
+
+#[test]
+fn inline_asm_with_indexed_output() {
+    let printed = roundtrip(
+        "
+        asm(\"nop\");
+        const main = fn (arg xs: u64*) -> void {
+            asm(\"mov\", in a = xs[0] + 1, out b = xs[1], out c = xs);
+        };
+        ",
+    );
+    assert!(printed.contains("out b = xs[1]"));
+}
+
+/// The float literals of `source`
+fn floats(source: &str) -> Vec<f64> {
+    struct Floats(Vec<f64>);
+    impl Visitor for Floats {
+        fn visit_exp(&mut self, exp: &Exp) {
+            if let Exp::Float(float) = exp {
+                self.0.push(float.num);
+            }
+            self.walk_exp(exp)
+        }
+    }
+    let mut floats = Floats(Vec::new());
+    floats.visit_comp_unit(&parse(source.to_string(), "test.cara".to_string()).unwrap());
+    floats.0
+}
+
+#[test]
+fn floats_that_overflow() {
+    let printed = roundtrip("const big = 1e999 + 2.5e308;");
+    assert_eq!(printed, "const big = 1e309 + 1e309;\n");
+    assert_eq!(floats(&printed), [f64::INFINITY, f64::INFINITY]);
+}
+
+#[test]
+fn floats_without_a_literal() {
+    use rasta::build::*;
+
+    let values = [
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        1e300,
+        5e-324,
+        2.0,
+    ];
+    let item = func("f", f64()).ret(values.into_iter().map(float).reduce(add).unwrap());
+    let printed = unparse(&unit([item.into()]));
+    assert!(
+        printed.contains("return 1e309 + -1e309 + (0.0 / 0.0) + 1e300 + 5e-324 + 2.0;"),
+        "{}",
+        printed
+    );
+    let reparsed = floats(&printed);
+    assert_eq!(
+        reparsed.iter().map(|num| num.to_bits()).collect::<Vec<_>>(),
+        [f64::INFINITY, f64::INFINITY, 0.0, 0.0, 1e300, 5e-324, 2.0].map(f64::to_bits)
+    );
+}
@@ -0,0 +1,152 @@
+use cara::parse;
+use rasta::*;
+
+/// Uses every kind of node the parser produces
+const SOURCE: &str = r#"
+[import](std)
+asm("nop", in a = 1, out b = c);
+#[top, inline]
+const Point = class { x: i32, y: i32, const origin = 0; };
+const putc = fn (arg c: i8) -> void;
+const limit = 1.5 + 0xFF;
+const main = fn (arg p: u64*, arg n: u64) -> u64 {
+    var xs: u64* = [1, 2, 3];
+    var ys: u64* = [0; 4];
+    var s: i8* = "hi";
+    const inner = fn () -> void { return; };
+    for i in (0, n, 1) {
+        if xs[i] > 1 { break; } else { continue; }
+    }
+    while *p != 0 { *p = *p - 1; }
+    (p)[1] = *(p + 1);
+    p = &xs;
+    { putc(!1); }
+    asm("ld", in a = ~n, out b = xs[1]);
+    ;
+    return (1 + 2) * 3 << 1 & 7;
+};
+"#;
+
+fn unit() -> CompUnit {
+    parse(SOURCE.to_string(), "test.cara".to_string()).unwrap()
+}
+
+/// Every span in a JSON encoded `CompUnit`, as `(file, start, end)`
+fn json_spans(value: &serde_json::Value, spans: &mut Vec<(u64, u64, u64)>) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            if let (Some(file), Some(bytes), 2) =
+                (fields.get("file"), fields.get("bytes"), fields.len())
+            {
+                spans.push((
+                    file.as_u64().unwrap(),
+                    bytes[0].as_u64().unwrap(),
+                    bytes[1].as_u64().unwrap(),
+                ));
+                return;
+            }
+            for (name, value) in fields {
+                if name != "source_map" {
+                    json_spans(value, spans);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                json_spans(value, spans);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The spans of `unit` as its JSON encoding has them, sorted
+fn all_spans(unit: &CompUnit) -> Vec<(u64, u64, u64)> {
+    let json = serde_json::from_str(&to_json(unit).unwrap()).unwrap();
+    let mut spans = Vec::new();
+    json_spans(&json, &mut spans);
+    spans.sort_unstable();
+    spans
+}
+
+fn key(span: &Span) -> (u64, u64, u64) {
+    let mut spans = Vec::new();
+    json_spans(&serde_json::to_value(span).unwrap(), &mut spans);
+    spans[0]
+}
+
+/// Moves every span one byte to the right
+struct Shift;
+
+fn shift(span: &Span) -> Span {
+    let bytes = span.byte_range();
+    Span::new(span.file(), bytes.start + 1..bytes.end + 1)
+}
+
+#[derive(Default)]
+struct Spans(Vec<(u64, u64, u64)>);
+
+impl Visitor for Spans {
+    fn visit_span(&mut self, span: &Span) {
+        self.0.push(key(span));
+    }
+}
+
+impl VisitorMut for Shift {
+    fn visit_span(&mut self, span: &mut Span) {
+        *span = shift(span);
+    }
+}
+
+impl Fold for Shift {
+    fn fold_span(&mut self, span: Span) -> Span {
+        shift(&span)
+    }
+}
+
+fn shifted(spans: Vec<(u64, u64, u64)>) -> Vec<(u64, u64, u64)> {
+    spans
+        .into_iter()
+        .map(|(file, start, end)| (file, start + 1, end + 1))
+        .collect()
+}
+
+#[test]
+fn visitor_reaches_every_span() {
+    let unit = unit();
+    let mut spans = Spans::default();
+    spans.visit_comp_unit(&unit);
+    spans.0.sort_unstable();
+    assert_eq!(spans.0, all_spans(&unit));
+}
+
+#[test]
+fn visitor_mut_reaches_every_span() {
+    let mut unit = unit();
+    let spans = all_spans(&unit);
+    Shift.visit_comp_unit(&mut unit);
+    assert_eq!(all_spans(&unit), shifted(spans));
+}
+
+#[test]
+fn fold_reaches_every_span() {
+    let unit = unit();
+    let spans = all_spans(&unit);
+    let folded = Shift.fold_comp_unit(unit);
+    assert_eq!(all_spans(&folded), shifted(spans));
+}
+
+#[test]
+fn fold_keeps_the_source_map_and_spans() {
+    struct Identity;
+    impl Fold for Identity {}
+
+    let unit = unit();
+    let json = to_json(&unit).unwrap();
+    let folded = Identity.fold_comp_unit(unit);
+    assert_eq!(to_json(&folded).unwrap(), json);
+
+    let file = folded.source_map.find_file("test.cara").unwrap();
+    assert_eq!(folded.source_map.file(file).unwrap().src(), SOURCE);
+    assert_eq!(unparse(&folded), unparse(&self::unit()));
+}
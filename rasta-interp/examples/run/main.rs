@@ -0,0 +1,72 @@
+use std::env::args;
+use std::fs;
+use std::process::exit;
+
+use cara::parse;
+use rasta::DiagnosticFormat;
+use rasta_interp::{ErrorKind, Interpreter, Value};
+
+fn main() {
+    let mut args = args();
+    let exe = args.next().unwrap();
+    let (Some(source_file_path), function, None) = (args.next(), args.next(), args.next()) else {
+        eprintln!("Usage: {} <source_file> [function]", exe);
+        exit(2);
+    };
+    let function = function.unwrap_or_else(|| "main".to_string());
+
+    let source = fs::read_to_string(&source_file_path).unwrap();
+    let ast = match parse(source, source_file_path) {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprint!(
+                "{}",
+                err.diagnostic
+                    .render(&err.source_map, DiagnosticFormat::Colored)
+            );
+            exit(1);
+        }
+    };
+
+    let mut interp = match Interpreter::new(&ast) {
+        Ok(interp) => interp,
+        Err(diagnostics) => {
+            for diagnostic in diagnostics {
+                eprint!(
+                    "{}",
+                    diagnostic.render(&ast.source_map, DiagnosticFormat::Colored)
+                );
+            }
+            exit(1);
+        }
+    };
+
+    // Let programs print by declaring these without a body
+    let registered = [
+        interp.register("puts", |text: String| println!("{}", text)),
+        interp.register("print", |num: i64| println!("{}", num)),
+    ];
+    for err in registered.into_iter().filter_map(Result::err) {
+        if !matches!(err.kind, ErrorKind::NotAProto(_)) {
+            eprint!(
+                "{}",
+                err.diagnostic()
+                    .render(&ast.source_map, DiagnosticFormat::Colored)
+            );
+            exit(1);
+        }
+    }
+
+    match interp.call(&function, Vec::new()) {
+        Ok(Value::Void) => {}
+        Ok(value) => println!("{}", value),
+        Err(err) => {
+            eprint!(
+                "{}",
+                err.diagnostic()
+                    .render(&ast.source_map, DiagnosticFormat::Colored)
+            );
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use super::*;
+
+/// Runs inline assembly blocks, to emulate the instructions of a target
+pub trait AsmHandler {
+    /// Run `asm` given the value of every `in` operand and the current value of every
+    /// `out` one, by name, returning the new values of the `out` operands in order
+    ///
+    /// The new values must have the types of the current ones, an `Err` fails the block
+    /// with the message.
+    fn run(
+        &mut self,
+        asm: &str,
+        inputs: &[(&str, Value)],
+        outputs: &[(&str, Value)],
+        memory: &mut Memory,
+    ) -> Result<Vec<Value>, String>;
+}
+
+impl<F> AsmHandler for F
+where
+    F: FnMut(&str, &[(&str, Value)], &[(&str, Value)], &mut Memory) -> Result<Vec<Value>, String>,
+{
+    fn run(
+        &mut self,
+        asm: &str,
+        inputs: &[(&str, Value)],
+        outputs: &[(&str, Value)],
+        memory: &mut Memory,
+    ) -> Result<Vec<Value>, String> {
+        self(asm, inputs, outputs, memory)
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    /// Run inline assembly blocks with `handler`, global ones before the first call
+    ///
+    /// ```
+    /// use rasta::build::*;
+    /// use rasta::Stmt;
+    /// use rasta_interp::{Interpreter, Memory, Value};
+    ///
+    /// let add = asm("add", [("a", var("x")), ("b", num(2))]);
+    /// let twice = func("twice", u64())
+    ///     .param("x", u64())
+    ///     .var("sum", u64(), num(0))
+    ///     .item(Stmt::InlineAsm(asm_out(add, "c", "sum")))
+    ///     .ret(var("sum"));
+    /// let unit = unit([twice.into()]);
+    ///
+    /// let mut interp = Interpreter::new(&unit).unwrap();
+    /// interp.set_asm_handler(
+    ///     |asm: &str, inputs: &[(&str, Value)], _: &[(&str, Value)], _: &mut Memory| {
+    ///         match (asm, inputs) {
+    ///             ("add", [(_, Value::Int(a, ty)), (_, Value::Int(b, _))]) => {
+    ///                 Ok(vec![Value::int(a + b, *ty)])
+    ///             }
+    ///             _ => Err(format!("unknown instruction `{}`", asm)),
+    ///         }
+    ///     },
+    /// );
+    /// assert_eq!(interp.call("twice", vec![Value::from(40u64)]).unwrap(), Value::from(42u64));
+    /// ```
+    pub fn set_asm_handler(&mut self, handler: impl AsmHandler + 'a) {
+        self.asm_handler = Some(Box::new(handler));
+    }
+
+    /// Run the global inline assembly blocks that didn't run yet, in order
+    pub(crate) fn exec_global_asm(&mut self) -> Result<(), Error> {
+        while let Some(asm) = self.global_asm.front().copied() {
+            self.frames.push(Frame::new(Region::Global));
+            let result = self.exec_asm(asm);
+            self.pop_frame();
+            result?;
+            self.global_asm.pop_front();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn exec_asm(&mut self, asm: &'a InlineAsm) -> Result<(), Error> {
+        if self.asm_handler.is_none() {
+            return Err(Error::new(ErrorKind::NoAsmHandler, asm.span.clone()));
+        }
+
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        let mut places = Vec::new();
+        for constraint in asm.constraints.iter() {
+            match constraint {
+                AsmConstraint::In(name, exp, _) => inputs.push((name.as_str(), self.eval(exp)?)),
+                AsmConstraint::Out(name, lval, span) => {
+                    let ptr = self.place(lval)?;
+                    outputs.push((name.as_str(), self.read(ptr, &lval.span)?));
+                    places.push((ptr, span));
+                }
+            }
+        }
+
+        let handler = self.asm_handler.as_mut().unwrap();
+        let values = handler
+            .run(&asm.asm, &inputs, &outputs, &mut self.memory)
+            .map_err(|message| Error::new(ErrorKind::Asm(message), asm.span.clone()))?;
+        if values.len() != outputs.len() {
+            let kind = ErrorKind::AsmOutputCount {
+                expected: outputs.len(),
+                found: values.len(),
+            };
+            return Err(Error::new(kind, asm.span.clone()));
+        }
+
+        for (((name, old), value), (ptr, span)) in outputs.into_iter().zip(values).zip(places) {
+            if !value.same_type(&old) {
+                let kind = ErrorKind::AsmOutputType(name.to_string());
+                return Err(Error::new(kind, span.clone()));
+            }
+            self.write(ptr, value, span)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use super::*;
+
+/// What went wrong while running a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// An integer `/` or `%` by zero
+    DivisionByZero,
+    /// A shift by a negative amount
+    NegativeShift,
+    /// A `for` loop stepping by zero, which would never end
+    ZeroStep,
+    /// An array of a negative length, or one too long to allocate
+    ArrayLength(BigInt),
+    /// A pointer read or written past the bounds of its allocation
+    OutOfBounds { index: i64, len: usize },
+    /// A pointer read or written after what it points to was freed
+    Dangling,
+    /// [`Memory::free`] of something that isn't the start of a heap allocation
+    InvalidFree,
+    /// Memory held a value of another type than the one read, like a string of floats
+    TypeMismatch { expected: String, found: Value },
+    /// [`Interpreter::call`] was given a name that is no global function
+    UnknownFunction(String),
+    /// A function got the wrong number of arguments
+    ArgumentCount { expected: usize, found: usize },
+    /// An argument doesn't have the type of its parameter
+    ArgumentType { index: usize, expected: String },
+    /// A function returning a value ran off its end
+    MissingReturn(String),
+    /// A function declared without a body was called, but no host function registered
+    NoBody(String),
+    /// A host function was registered for a name that is no global function without a
+    /// body
+    NotAProto(String),
+    /// A host function takes another number of arguments than its declaration
+    HostArity { name: String, params: usize },
+    /// A host function doesn't convert values of a type of the declaration
+    HostType { name: String, ty: String },
+    /// A raw host function returned a value of the wrong type
+    HostReturn { name: String, expected: String },
+    /// A host function failed
+    Host(String),
+    /// `break` or `continue` outside of a loop
+    TerminatorOutsideLoop,
+    /// A constant whose value depends on itself
+    CyclicConstant(String),
+    /// A nested function used a local of the function around it
+    Capture(String),
+    /// An inline assembly block ran without an [`AsmHandler`]
+    NoAsmHandler,
+    /// The [`AsmHandler`] failed
+    Asm(String),
+    /// The [`AsmHandler`] returned another number of values than there are `out` operands
+    AsmOutputCount { expected: usize, found: usize },
+    /// The [`AsmHandler`] returned a value of the wrong type for an `out` operand
+    AsmOutputType(String),
+    /// The program took more steps than [`Limits::fuel`]
+    OutOfFuel,
+    /// Calls nested deeper than [`Limits::max_depth`]
+    CallDepth(usize),
+    /// Memory would hold more bytes than [`Limits::max_memory`]
+    OutOfMemory(usize),
+    /// The program ran past [`Limits::deadline`]
+    Deadline,
+    /// Something the interpreter can't run yet
+    Unsupported(String),
+    /// Something the type checker should have rejected, a bug of the checker
+    Internal(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::NegativeShift => write!(f, "shift by a negative amount"),
+            Self::ZeroStep => write!(f, "`for` loop with a step of zero"),
+            Self::ArrayLength(len) => write!(f, "an array cannot have a length of {}", len),
+            Self::OutOfBounds { index, len } => write!(
+                f,
+                "index {} is out of bounds of {} element{}",
+                index,
+                len,
+                if *len == 1 { "" } else { "s" }
+            ),
+            Self::Dangling => write!(f, "use of freed memory"),
+            Self::InvalidFree => write!(f, "only the start of a heap allocation can be freed"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, found `{}`", expected, found)
+            }
+            Self::UnknownFunction(name) => write!(f, "no function named `{}`", name),
+            Self::ArgumentCount { expected, found } => write!(
+                f,
+                "expected {} argument{}, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            Self::ArgumentType { index, expected } => {
+                write!(f, "argument {} must be a `{}`", index + 1, expected)
+            }
+            Self::MissingReturn(name) => write!(f, "`{}` ended without returning a value", name),
+            Self::NoBody(name) => write!(f, "`{}` has no body", name),
+            Self::NotAProto(name) => write!(f, "no function named `{}` without a body", name),
+            Self::HostArity { name, params } => write!(
+                f,
+                "the host function for `{}` takes {} argument{}",
+                name,
+                params,
+                if *params == 1 { "" } else { "s" }
+            ),
+            Self::HostType { name, ty } => write!(
+                f,
+                "the host function for `{}` doesn't convert `{}` values",
+                name, ty
+            ),
+            Self::HostReturn { name, expected } => write!(
+                f,
+                "the host function for `{}` didn't return a `{}`",
+                name, expected
+            ),
+            Self::Host(message) => write!(f, "{}", message),
+            Self::TerminatorOutsideLoop => write!(f, "`break` or `continue` outside of a loop"),
+            Self::CyclicConstant(name) => write!(f, "the value of `{}` depends on itself", name),
+            Self::Capture(name) => write!(
+                f,
+                "`{}` belongs to another function and can't be used here",
+                name
+            ),
+            Self::NoAsmHandler => write!(f, "no handler for inline assembly"),
+            Self::Asm(message) => write!(f, "{}", message),
+            Self::AsmOutputCount { expected, found } => write!(
+                f,
+                "expected {} `out` value{} from the inline assembly handler, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            Self::AsmOutputType(name) => write!(
+                f,
+                "the inline assembly handler returned a value of the wrong type for `{}`",
+                name
+            ),
+            Self::OutOfFuel => write!(f, "ran out of fuel"),
+            Self::CallDepth(max) => write!(f, "calls nested deeper than {} levels", max),
+            Self::OutOfMemory(max) => write!(f, "memory would hold more than {} bytes", max),
+            Self::Deadline => write!(f, "ran past the deadline"),
+            Self::Unsupported(what) => write!(f, "{} are not supported yet", what),
+            Self::Internal(what) => write!(f, "internal error: the type checker let {}", what),
+        }
+    }
+}
+
+/// A runtime error, at the part of the program that caused it
+#[derive(Debug, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// The error as a diagnostic that can be rendered with the source map of the AST
+    pub fn diagnostic(&self) -> Diagnostic {
+        let label = match &self.kind {
+            ErrorKind::DivisionByZero => "the divisor is zero",
+            ErrorKind::NegativeShift => "the amount is negative",
+            ErrorKind::ZeroStep => "the step is zero",
+            ErrorKind::ArrayLength(_) => "allocated here",
+            ErrorKind::OutOfBounds { .. } => "accessed here",
+            ErrorKind::Dangling => "the pointer outlived what it points to",
+            ErrorKind::InvalidFree => "freed here",
+            ErrorKind::TypeMismatch { .. } => "read here",
+            ErrorKind::UnknownFunction(_) => "",
+            ErrorKind::ArgumentCount { .. } => "the parameters are declared here",
+            ErrorKind::ArgumentType { .. } => "the parameter is declared here",
+            ErrorKind::MissingReturn(_) => "no `return` reached",
+            ErrorKind::NoBody(_) => "called here",
+            ErrorKind::NotAProto(_) => "",
+            ErrorKind::HostArity { .. } => "the parameters are declared here",
+            ErrorKind::HostType { .. } => "declared here",
+            ErrorKind::HostReturn { .. } | ErrorKind::Host(_) => "called here",
+            ErrorKind::TerminatorOutsideLoop => "not in a loop",
+            ErrorKind::CyclicConstant(_) => "used while being evaluated",
+            ErrorKind::Capture(_) => "used here",
+            ErrorKind::NoAsmHandler | ErrorKind::Asm(_) | ErrorKind::AsmOutputCount { .. } => {
+                "in this block"
+            }
+            ErrorKind::AsmOutputType(_) => "for this operand",
+            ErrorKind::OutOfFuel | ErrorKind::Deadline => "stopped here",
+            ErrorKind::CallDepth(_) => "called here",
+            ErrorKind::OutOfMemory(_) => "needed here",
+            ErrorKind::Unsupported(_) => "used here",
+            ErrorKind::Internal(_) => "here",
+        };
+        let diagnostic = Diagnostic::error(self.kind.to_string());
+        // Errors that aren't caused by the program have no span to point at
+        if self.span.is_dummy() {
+            return diagnostic;
+        }
+        diagnostic.with_primary(self.span.clone(), label)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use rasta::num_bigint::Sign;
+
+use super::*;
+
+impl<'a> Interpreter<'a> {
+    /// The type the checker inferred for `exp`
+    fn exp_type(&self, exp: &Exp) -> Result<&VType, Error> {
+        self.ids
+            .exp(exp)
+            .and_then(|id| self.types.types.get(id))
+            .ok_or_else(|| internal("an expression through without a type", &exp.full_span()))
+    }
+
+    pub(crate) fn eval(&mut self, exp: &'a Exp) -> Result<Value, Error> {
+        self.step(|| exp.full_span())?;
+        grow_stack(|| self.eval_exp(exp))
+    }
+
+    fn eval_exp(&mut self, exp: &'a Exp) -> Result<Value, Error> {
+        match exp {
+            Exp::Exp(exp, _) => self.eval(exp),
+            Exp::Number(number) => {
+                let ty = IntType::of(&self.exp_type(exp)?.ty)
+                    .ok_or_else(|| internal("through an integer that isn't one", &number.span))?;
+                Ok(Value::int(number.num.clone(), ty))
+            }
+            Exp::Float(float) => Ok(match self.exp_type(exp)?.ty {
+                VTypeEnum::F32 => Value::F32(float.num as f32),
+                _ => Value::F64(float.num),
+            }),
+            Exp::LVal(lval) => {
+                let ptr = self.place(lval)?;
+                self.read(ptr, &lval.span)
+            }
+            Exp::FuncCall(call) => {
+                let symbol = self.resolution.uses[self.ids.exp(exp).unwrap()];
+                let decl = match self.resolution.symbol(symbol).kind {
+                    SymbolKind::Const(id) => self.funcs.get(&id).copied(),
+                    _ => None,
+                };
+                let decl = decl.ok_or_else(|| not_a_function(&call.span))?;
+
+                let mut args = Vec::with_capacity(call.args.len());
+                for arg in call.args.iter() {
+                    args.push(self.eval(arg)?);
+                }
+                self.call_decl(decl, args, &call.span)
+            }
+            Exp::Unary(op, operand, _) => {
+                let value = self.eval(operand)?;
+                self.reserve_result(&value, exp)?;
+                unary(op, value).map_err(|kind| Error::new(kind, exp.full_span()))
+            }
+            Exp::Binary(lhs, op, rhs, _) => {
+                let lhs_value = self.eval(lhs)?;
+                // `&&` and `||` only look at the right operand if the left one doesn't
+                // decide the result
+                match op {
+                    BinaryOp::And if !lhs_value.is_true() => return Ok(Value::Bool(false)),
+                    BinaryOp::Or if lhs_value.is_true() => return Ok(Value::Bool(true)),
+                    BinaryOp::And | BinaryOp::Or => {
+                        return Ok(Value::Bool(self.eval(rhs)?.is_true()))
+                    }
+                    _ => {}
+                }
+                let rhs_value = self.eval(rhs)?;
+                self.reserve_result(&lhs_value, exp)?;
+                binary(op, lhs_value, rhs_value).map_err(|kind| Error::new(kind, exp.full_span()))
+            }
+            Exp::Str(string, _) => {
+                let id = self.ids.exp(exp).unwrap();
+                if let Some(ptr) = self.strings.get(&id) {
+                    return Ok(Value::Ptr(*ptr));
+                }
+                // Strings are static arrays of `i8`s ending with a zero
+                let ty = IntType::of(&VTypeEnum::I8).unwrap();
+                let bytes = string.bytes().chain([0]);
+                let chars = bytes.map(|byte| Value::int(byte, ty)).collect();
+                let ptr = self.alloc(Region::Global, chars, &exp.get_span())?;
+                self.strings.insert(id, ptr);
+                Ok(Value::Ptr(ptr))
+            }
+            Exp::GetAddr(get_addr) => Ok(Value::Ptr(self.place(&get_addr.lval)?)),
+            Exp::Deref(deref) => {
+                let ptr = self.deref_place(deref)?;
+                self.read(ptr, &deref.get_span())
+            }
+            Exp::Array(array) => self.eval_array(exp, array),
+        }
+    }
+
+    /// Check that the result of an operation on `value` fits in memory before computing
+    /// it, as wide integers take long to compute
+    fn reserve_result(&self, value: &Value, exp: &Exp) -> Result<(), Error> {
+        self.memory
+            .reserve(value.size())
+            .map_err(|kind| Error::new(kind, exp.full_span()))
+    }
+
+    /// Allocate the elements of `array` in the current frame, pointing to the first one
+    fn eval_array(&mut self, exp: &'a Exp, array: &'a Array) -> Result<Value, Error> {
+        let values = match array {
+            Array::List(values, _) => {
+                let mut list = Vec::with_capacity(values.len());
+                for value in values.iter() {
+                    list.push(self.eval(value)?);
+                }
+                list
+            }
+            Array::Template(value, len, _) => {
+                let value = self.eval(value)?;
+                let Value::Int(len, _) = self.eval(&len.exp)? else {
+                    return Err(internal(
+                        "through an array length that isn't an integer",
+                        &len.exp.full_span(),
+                    ));
+                };
+                let Ok(len) = usize::try_from(&len) else {
+                    return Err(Error::new(ErrorKind::ArrayLength(len), array.get_span()));
+                };
+                // Check the limit before making room for all of them
+                self.memory
+                    .reserve(len.saturating_mul(value.size()))
+                    .map_err(|kind| Error::new(kind, array.get_span()))?;
+                vec![value; len]
+            }
+        };
+
+        let id = self.ids.exp(exp).unwrap();
+        if let Some(ptr) = self.frame().arrays.get(&id).copied() {
+            if self.memory.len(ptr) == Some(values.len()) {
+                for (index, value) in values.into_iter().enumerate() {
+                    self.memory.write(ptr.offset(index as i64), value).unwrap();
+                }
+                return Ok(Value::Ptr(ptr));
+            }
+            self.memory.release(ptr);
+        }
+        let region = self.frame().region;
+        let ptr = self.alloc(region, values, &array.get_span())?;
+        if region == Region::Stack {
+            self.frame().arrays.insert(id, ptr);
+        }
+        Ok(Value::Ptr(ptr))
+    }
+}
+
+fn unary(op: &UnaryOp, value: Value) -> Result<Value, ErrorKind> {
+    Ok(match (op, value) {
+        (UnaryOp::Positive, value) => value,
+        (UnaryOp::Negative, Value::Int(num, ty)) => Value::int(-num, ty),
+        (UnaryOp::Negative, Value::F32(num)) => Value::F32(-num),
+        (UnaryOp::Negative, Value::F64(num)) => Value::F64(-num),
+        (UnaryOp::Not, value) => Value::Bool(!value.is_true()),
+        (UnaryOp::BitNot, Value::Int(num, ty)) => Value::int(!num, ty),
+        (UnaryOp::BitNot, Value::Bool(value)) => Value::Bool(!value),
+        (op, value) => return Err(ill_typed(format!("`{:?}` of {}", op, value))),
+    })
+}
+
+/// Apply `op` to operands of the same type, except for shifts. Integers wrap around,
+/// shifting by their width or more shifts every bit out.
+fn binary(op: &BinaryOp, lhs: Value, rhs: Value) -> Result<Value, ErrorKind> {
+    if op.is_comparison() {
+        let ordering = match (&lhs, &rhs) {
+            (Value::Bool(lhs), Value::Bool(rhs)) => lhs.partial_cmp(rhs),
+            (Value::Int(lhs, _), Value::Int(rhs, _)) => lhs.partial_cmp(rhs),
+            (Value::F32(lhs), Value::F32(rhs)) => lhs.partial_cmp(rhs),
+            (Value::F64(lhs), Value::F64(rhs)) => lhs.partial_cmp(rhs),
+            (Value::Ptr(lhs), Value::Ptr(rhs)) => lhs.partial_cmp(rhs),
+            _ => return Err(ill_typed(format!("comparing {} and {}", lhs, rhs))),
+        };
+        // Every comparison with NaN is false, but `!=`
+        let result = match (op, ordering) {
+            (BinaryOp::Neq, ordering) => ordering != Some(Ordering::Equal),
+            (_, None) => false,
+            (BinaryOp::Lt, Some(ordering)) => ordering.is_lt(),
+            (BinaryOp::Gt, Some(ordering)) => ordering.is_gt(),
+            (BinaryOp::Le, Some(ordering)) => ordering.is_le(),
+            (BinaryOp::Ge, Some(ordering)) => ordering.is_ge(),
+            (_, Some(ordering)) => ordering.is_eq(),
+        };
+        return Ok(Value::Bool(result));
+    }
+
+    Ok(match (lhs, rhs) {
+        (Value::Int(lhs, ty), Value::Int(rhs, _)) => {
+            let num = match op {
+                BinaryOp::Add => lhs + rhs,
+                BinaryOp::Sub => lhs - rhs,
+                BinaryOp::Mul => lhs * rhs,
+                BinaryOp::Div | BinaryOp::Mod if rhs.sign() == Sign::NoSign => {
+                    return Err(ErrorKind::DivisionByZero)
+                }
+                // Both round towards zero
+                BinaryOp::Div => lhs / rhs,
+                BinaryOp::Mod => lhs % rhs,
+                BinaryOp::BitAnd => lhs & rhs,
+                BinaryOp::BitOr => lhs | rhs,
+                BinaryOp::BitXor => lhs ^ rhs,
+                BinaryOp::Shl | BinaryOp::Shr => {
+                    if rhs.sign() == Sign::Minus {
+                        return Err(ErrorKind::NegativeShift);
+                    }
+                    // Past the width every bit is shifted out
+                    let amount = u32::try_from(rhs).unwrap_or(u32::MAX).min(ty.bits) as usize;
+                    match op {
+                        BinaryOp::Shl => lhs << amount,
+                        // Rounds towards negative infinity, so signed values keep their sign
+                        _ => lhs >> amount,
+                    }
+                }
+                _ => return Err(ill_typed(format!("`{:?}` of integers", op))),
+            };
+            Value::int(num, ty)
+        }
+        (Value::Bool(lhs), Value::Bool(rhs)) => Value::Bool(match op {
+            BinaryOp::BitAnd => lhs & rhs,
+            BinaryOp::BitOr => lhs | rhs,
+            BinaryOp::BitXor => lhs ^ rhs,
+            _ => return Err(ill_typed(format!("`{:?}` of `bool`s", op))),
+        }),
+        (Value::F32(lhs), Value::F32(rhs)) => Value::F32(float(op, lhs, rhs)?),
+        (Value::F64(lhs), Value::F64(rhs)) => Value::F64(float(op, lhs, rhs)?),
+        // Pointers move by elements
+        (Value::Ptr(ptr), Value::Int(count, _)) => Value::Ptr(match op {
+            BinaryOp::Add => ptr.offset(to_offset(&count)),
+            BinaryOp::Sub => ptr.offset(to_offset(&-count)),
+            _ => return Err(ill_typed(format!("`{:?}` of a pointer", op))),
+        }),
+        (lhs, rhs) => return Err(ill_typed(format!("`{:?}` of {} and {}", op, lhs, rhs))),
+    })
+}
+
+/// IEEE 754 arithmetic, dividing by zero gives an infinity or NaN
+fn float<T>(op: &BinaryOp, lhs: T, rhs: T) -> Result<T, ErrorKind>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T> + Rem<Output = T>,
+{
+    Ok(match op {
+        BinaryOp::Add => lhs + rhs,
+        BinaryOp::Sub => lhs - rhs,
+        BinaryOp::Mul => lhs * rhs,
+        BinaryOp::Div => lhs / rhs,
+        BinaryOp::Mod => lhs % rhs,
+        _ => return Err(ill_typed(format!("`{:?}` of floats", op))),
+    })
+}
+
+fn ill_typed(what: String) -> ErrorKind {
+    ErrorKind::Internal(format!("through {}", what))
+}
@@ -0,0 +1,395 @@
+use std::fmt::Display;
+
+use super::*;
+
+/// A Rust function called for a function declared without a body
+pub(crate) type RawHostFn<'a> =
+    Box<dyn FnMut(&mut Memory, Vec<Value>) -> Result<Value, ErrorKind> + 'a>;
+
+/// A Rust type host functions take or return for values of the interpreted program
+pub trait HostValue: Sized {
+    /// Whether values of type `ty` convert to and from `Self`
+    fn fits(ty: &VType) -> bool;
+
+    /// Convert a value of a type `Self` fits
+    fn from_value(value: Value, memory: &Memory) -> Result<Self, ErrorKind>;
+
+    /// Convert to a value of `ty`, a type `Self` fits
+    fn into_value(self, ty: &VType, memory: &mut Memory) -> Result<Value, ErrorKind>;
+}
+
+macro_rules! host_int {
+    ($($ty:ty),*) => {
+        $(
+            impl HostValue for $ty {
+                fn fits(ty: &VType) -> bool {
+                    let Value::Int(_, int) = Value::from(0 as $ty) else {
+                        unreachable!();
+                    };
+                    !ty.is_pointer() && IntType::of(&ty.ty) == Some(int)
+                }
+
+                fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+                    match value {
+                        Value::Int(num, _) => Ok(<$ty>::try_from(num).unwrap()),
+                        value => unreachable!("{:?} doesn't fit", value),
+                    }
+                }
+
+                fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+                    Ok(Value::from(self))
+                }
+            }
+        )*
+    };
+}
+
+host_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Integers of any width, wrapped around into the range of their type when returned
+impl HostValue for BigInt {
+    fn fits(ty: &VType) -> bool {
+        ty.is_integer()
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        match value {
+            Value::Int(num, _) => Ok(num),
+            value => unreachable!("{:?} doesn't fit", value),
+        }
+    }
+
+    fn into_value(self, ty: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::int(self, IntType::of(&ty.ty).unwrap()))
+    }
+}
+
+impl HostValue for bool {
+    fn fits(ty: &VType) -> bool {
+        ty.is_bool()
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        Ok(value.is_true())
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::Bool(self))
+    }
+}
+
+impl HostValue for f32 {
+    fn fits(ty: &VType) -> bool {
+        !ty.is_pointer() && ty.ty == VTypeEnum::F32
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        match value {
+            Value::F32(num) => Ok(num),
+            value => unreachable!("{:?} doesn't fit", value),
+        }
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::F32(self))
+    }
+}
+
+impl HostValue for f64 {
+    fn fits(ty: &VType) -> bool {
+        !ty.is_pointer() && ty.ty == VTypeEnum::F64
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        match value {
+            Value::F64(num) => Ok(num),
+            value => unreachable!("{:?} doesn't fit", value),
+        }
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::F64(self))
+    }
+}
+
+/// Nothing, for `void` functions
+impl HostValue for () {
+    fn fits(ty: &VType) -> bool {
+        ty.is_void()
+    }
+
+    fn from_value(_: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        Ok(())
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::Void)
+    }
+}
+
+/// Pointers of any type, to follow with [`Memory`] in [`Interpreter::register_raw`]
+impl HostValue for Pointer {
+    fn fits(ty: &VType) -> bool {
+        ty.is_pointer()
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        match value {
+            Value::Ptr(ptr) => Ok(ptr),
+            value => unreachable!("{:?} doesn't fit", value),
+        }
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(Value::Ptr(self))
+    }
+}
+
+/// `i8*` strings ending with a zero, returned strings are allocated on the heap
+impl HostValue for String {
+    fn fits(ty: &VType) -> bool {
+        ty.ty == VTypeEnum::I8 && ty.star == 1
+    }
+
+    fn from_value(value: Value, memory: &Memory) -> Result<Self, ErrorKind> {
+        memory.read_str(Pointer::from_value(value, memory)?)
+    }
+
+    fn into_value(self, _: &VType, memory: &mut Memory) -> Result<Value, ErrorKind> {
+        let ty = IntType::of(&VTypeEnum::I8).unwrap();
+        let bytes = self.bytes().chain([0]);
+        let chars = bytes.map(|byte| Value::int(byte, ty)).collect();
+        Ok(Value::Ptr(memory.alloc(Region::Heap, chars)?))
+    }
+}
+
+/// Any value, which must have the type it's converted to
+impl HostValue for Value {
+    fn fits(_: &VType) -> bool {
+        true
+    }
+
+    fn from_value(value: Value, _: &Memory) -> Result<Self, ErrorKind> {
+        Ok(value)
+    }
+
+    fn into_value(self, _: &VType, _: &mut Memory) -> Result<Value, ErrorKind> {
+        Ok(self)
+    }
+}
+
+/// What host functions return, a [`HostValue`] or a `Result` of one to fail the call
+pub trait HostReturn {
+    /// Whether `ty` is a return type the value converts to
+    fn fits(ty: &VType) -> bool;
+
+    fn into_result(self, ty: &VType, memory: &mut Memory) -> Result<Value, ErrorKind>;
+}
+
+impl<T: HostValue> HostReturn for T {
+    fn fits(ty: &VType) -> bool {
+        T::fits(ty)
+    }
+
+    fn into_result(self, ty: &VType, memory: &mut Memory) -> Result<Value, ErrorKind> {
+        self.into_value(ty, memory)
+    }
+}
+
+impl<T: HostValue, E: Display> HostReturn for Result<T, E> {
+    fn fits(ty: &VType) -> bool {
+        T::fits(ty)
+    }
+
+    fn into_result(self, ty: &VType, memory: &mut Memory) -> Result<Value, ErrorKind> {
+        match self {
+            Ok(value) => value.into_value(ty, memory),
+            Err(err) => Err(ErrorKind::Host(err.to_string())),
+        }
+    }
+}
+
+/// A Rust closure taking [`HostValue`]s, `Args` is the tuple of their types
+pub trait HostFn<Args> {
+    /// Whether the value of each parameter converts from its type
+    fn params() -> Vec<fn(&VType) -> bool>;
+
+    /// Whether `ty` is a return type the result converts to
+    fn ret(ty: &VType) -> bool;
+
+    fn call(
+        &mut self,
+        args: Vec<Value>,
+        ret: &VType,
+        memory: &mut Memory,
+    ) -> Result<Value, ErrorKind>;
+}
+
+macro_rules! host_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> HostFn<($($arg,)*)> for F
+        where
+            F: FnMut($($arg),*) -> R,
+            R: HostReturn,
+            $($arg: HostValue,)*
+        {
+            fn params() -> Vec<fn(&VType) -> bool> {
+                vec![$($arg::fits),*]
+            }
+
+            fn ret(ty: &VType) -> bool {
+                R::fits(ty)
+            }
+
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(
+                &mut self,
+                args: Vec<Value>,
+                ret: &VType,
+                memory: &mut Memory,
+            ) -> Result<Value, ErrorKind> {
+                let mut args = args.into_iter();
+                $(let $arg = $arg::from_value(args.next().unwrap(), memory)?;)*
+                self($($arg),*).into_result(ret, memory)
+            }
+        }
+    };
+}
+
+host_fn!();
+host_fn!(A);
+host_fn!(A, B);
+host_fn!(A, B, C);
+host_fn!(A, B, C, D);
+host_fn!(A, B, C, D, E);
+host_fn!(A, B, C, D, E, G);
+
+impl<'a> Interpreter<'a> {
+    /// Run `host` for calls of the global function `name`, declared without a body
+    ///
+    /// The parameters and return type of the declaration must convert to and from the
+    /// ones of `host`:
+    ///
+    /// ```
+    /// use rasta::build::*;
+    /// use rasta_interp::{Interpreter, Value};
+    ///
+    /// let shout = proto("shout", ptr(i8())).param("text", ptr(i8()));
+    /// let greet = func("greet", ptr(i8())).ret(call("shout", [string("hello")]));
+    /// let unit = unit([shout.into(), greet.into()]);
+    ///
+    /// let mut interp = Interpreter::new(&unit).unwrap();
+    /// interp
+    ///     .register("shout", |text: String| text.to_uppercase())
+    ///     .unwrap();
+    /// let Value::Ptr(greeting) = interp.call("greet", vec![]).unwrap() else {
+    ///     unreachable!();
+    /// };
+    /// assert_eq!(interp.memory().read_str(greeting).unwrap(), "HELLO");
+    /// ```
+    pub fn register<Args, F>(&mut self, name: &str, mut host: F) -> Result<(), Error>
+    where
+        F: HostFn<Args> + 'a,
+    {
+        let (decl, proto) = self.proto(name)?;
+
+        let fits = F::params();
+        if fits.len() != proto.params.len() {
+            let kind = ErrorKind::HostArity {
+                name: name.to_string(),
+                params: fits.len(),
+            };
+            return Err(Error::new(kind, params_span(&proto.params, &decl.span)));
+        }
+        for (param, fits) in proto.params.iter().zip(fits) {
+            if !fits(&param.ty) {
+                let kind = ErrorKind::HostType {
+                    name: name.to_string(),
+                    ty: type_name(&param.ty),
+                };
+                return Err(Error::new(kind, param.span.clone()));
+            }
+        }
+        if !F::ret(&proto.func_type) {
+            let kind = ErrorKind::HostType {
+                name: name.to_string(),
+                ty: type_name(&proto.func_type),
+            };
+            return Err(Error::new(kind, proto.func_type.span.clone()));
+        }
+
+        let ret = proto.func_type.clone();
+        let raw: RawHostFn<'a> = Box::new(move |memory, args| host.call(args, &ret, memory));
+        self.hosts.insert(self.ids.const_decl(decl).unwrap(), raw);
+        Ok(())
+    }
+
+    /// Run `host` for calls of the global function `name`, declared without a body, with
+    /// the arguments as they are and the memory they may point into
+    ///
+    /// The value returned must have the return type of the declaration, an `Err` fails the
+    /// call with the message.
+    pub fn register_raw<F>(&mut self, name: &str, mut host: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut Memory, Vec<Value>) -> Result<Value, String> + 'a,
+    {
+        let (decl, _) = self.proto(name)?;
+        let raw: RawHostFn<'a> =
+            Box::new(move |memory, args| host(memory, args).map_err(ErrorKind::Host));
+        self.hosts.insert(self.ids.const_decl(decl).unwrap(), raw);
+        Ok(())
+    }
+
+    fn proto(&self, name: &str) -> Result<(&'a ConstDecl, &'a ProtoDef), Error> {
+        let unit: &'a CompUnit = self.unit;
+        unit.global_items
+            .iter()
+            .find_map(|item| match item {
+                GlobalItem::ConstDecl(
+                    decl @ ConstDecl {
+                        init: ConstInitVal::Proto(proto),
+                        ..
+                    },
+                ) if decl.id == name => Some((decl, proto)),
+                _ => None,
+            })
+            .ok_or_else(|| Error::new(ErrorKind::NotAProto(name.to_string()), Span::dummy()))
+    }
+
+    /// Call the host function registered for `decl`
+    pub(crate) fn call_host(
+        &mut self,
+        decl: &'a ConstDecl,
+        proto: &'a ProtoDef,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Result<Value, Error> {
+        let id = self.ids.const_decl(decl).unwrap();
+        let Some(host) = self.hosts.get_mut(&id) else {
+            return Err(Error::new(ErrorKind::NoBody(decl.id.clone()), span.clone()));
+        };
+        let value = host(&mut self.memory, args).map_err(|kind| Error::new(kind, span.clone()))?;
+        if !value.has_type(&proto.func_type) {
+            let kind = ErrorKind::HostReturn {
+                name: decl.id.clone(),
+                expected: type_name(&proto.func_type),
+            };
+            return Err(Error::new(kind, span.clone()));
+        }
+        Ok(value)
+    }
+}
+
+/// The span of the parameter list, the whole function if it has none
+pub(crate) fn params_span(params: &[Param], span: &Span) -> Span {
+    match (params.first(), params.last()) {
+        (Some(first), Some(last)) => first.span.to(&last.span),
+        _ => span.clone(),
+    }
+}
+
+pub(crate) fn type_name(ty: &VType) -> String {
+    let mut printer = Printer::new();
+    printer.print_vtype(ty);
+    printer.finish()
+}
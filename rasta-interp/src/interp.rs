@@ -0,0 +1,604 @@
+use std::collections::{HashMap, VecDeque};
+
+use rasta::num_bigint::Sign;
+
+use super::*;
+
+/// Runs the functions of a [`CompUnit`]
+///
+/// The unit is resolved and type checked up front, so values always have the types the
+/// checker inferred. Global constants are evaluated when first used. Every variable lives
+/// in [`Memory`], so pointers can be taken to it.
+pub struct Interpreter<'a> {
+    pub(crate) unit: &'a CompUnit,
+    pub(crate) ids: NodeIds<'a>,
+    pub(crate) resolution: Resolution,
+    pub(crate) types: TypeCheck,
+    /// Every constant declaring a function or proto, global or local
+    pub(crate) funcs: HashMap<NodeId, &'a ConstDecl>,
+    /// The symbol every definition introduces
+    pub(crate) defs: HashMap<SymbolKind, SymbolId>,
+    pub(crate) globals: HashMap<SymbolId, Global<'a>>,
+    /// The host function registered for every function declared without a body
+    pub(crate) hosts: HashMap<NodeId, RawHostFn<'a>>,
+    pub(crate) asm_handler: Option<Box<dyn AsmHandler + 'a>>,
+    /// The global inline assembly blocks that didn't run yet
+    pub(crate) global_asm: VecDeque<&'a InlineAsm>,
+    /// The allocation of every string literal evaluated so far
+    pub(crate) strings: HashMap<NodeId, Pointer>,
+    pub(crate) frames: Vec<Frame>,
+    pub(crate) memory: Memory,
+    pub(crate) limits: Limits,
+    /// The steps left, `None` if they're unlimited
+    pub(crate) fuel: Option<u64>,
+    /// The steps taken so far
+    pub(crate) steps: u64,
+    /// The number of calls running
+    pub(crate) depth: usize,
+}
+
+pub(crate) enum Global<'a> {
+    Pending(&'a Exp),
+    Evaluating,
+    Done(Pointer),
+}
+
+/// The locals of a running function, or of a global being evaluated
+pub(crate) struct Frame {
+    /// Where the frame allocates, only `Stack` frames are freed when popped
+    pub(crate) region: Region,
+    pub(crate) locals: HashMap<SymbolId, Pointer>,
+    /// The allocation of every array literal evaluated in the frame, an array evaluated
+    /// again reuses it like C compound literals do
+    pub(crate) arrays: HashMap<NodeId, Pointer>,
+}
+
+impl Frame {
+    pub(crate) fn new(region: Region) -> Self {
+        Self {
+            region,
+            locals: HashMap::new(),
+            arrays: HashMap::new(),
+        }
+    }
+}
+
+/// How a statement ended
+pub(crate) enum Flow {
+    Next,
+    Break(Span),
+    Continue(Span),
+    Return(Value),
+}
+
+impl<'a> Interpreter<'a> {
+    /// Check `unit` and prepare to run it, or return the problems found
+    pub fn new(unit: &'a CompUnit) -> Result<Self, Vec<Diagnostic>> {
+        let ids = NodeIds::new(unit);
+        let resolution = resolve(unit, &ids);
+        let types = type_check(unit, &ids, &resolution);
+        if !resolution.is_ok() || !types.is_ok() {
+            let mut diagnostics = resolution.diagnostics;
+            diagnostics.extend(types.diagnostics);
+            return Err(diagnostics);
+        }
+
+        let defs = resolution
+            .iter()
+            .map(|(id, symbol)| (symbol.kind, id))
+            .collect();
+        let mut interp = Self {
+            unit,
+            ids,
+            resolution,
+            types,
+            funcs: HashMap::new(),
+            defs,
+            globals: HashMap::new(),
+            hosts: HashMap::new(),
+            asm_handler: None,
+            global_asm: VecDeque::new(),
+            strings: HashMap::new(),
+            frames: Vec::new(),
+            memory: Memory::default(),
+            limits: Limits::default(),
+            fuel: None,
+            steps: 0,
+            depth: 0,
+        };
+
+        for item in unit.global_items.iter() {
+            match item {
+                GlobalItem::ConstDecl(decl) => {
+                    if let ConstInitVal::Exp(exp) = &decl.init {
+                        let symbol = interp.const_symbol(decl);
+                        interp.globals.insert(symbol, Global::Pending(&exp.exp));
+                    }
+                    interp.collect_funcs(decl);
+                }
+                GlobalItem::InlineAsm(asm) => interp.global_asm.push_back(asm),
+                GlobalItem::BuiltinFnCall(_) => {}
+            }
+        }
+        Ok(interp)
+    }
+
+    /// Call the global function `name`
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, Error> {
+        let decl = self.unit.global_items.iter().find_map(|item| match item {
+            GlobalItem::ConstDecl(decl) if decl.id == name => Some(decl),
+            _ => None,
+        });
+        let params = match decl.map(|decl| &decl.init) {
+            Some(ConstInitVal::Function(func)) => &func.params,
+            Some(ConstInitVal::Proto(proto)) => &proto.params,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::UnknownFunction(name.to_string()),
+                    Span::dummy(),
+                ))
+            }
+        };
+        let decl = decl.unwrap();
+
+        if params.len() != args.len() {
+            let kind = ErrorKind::ArgumentCount {
+                expected: params.len(),
+                found: args.len(),
+            };
+            return Err(Error::new(kind, params_span(params, &decl.span)));
+        }
+        for (index, (param, arg)) in params.iter().zip(&args).enumerate() {
+            if !arg.has_type(&param.ty) {
+                let kind = ErrorKind::ArgumentType {
+                    index,
+                    expected: type_name(&param.ty),
+                };
+                return Err(Error::new(kind, param.span.clone()));
+            }
+        }
+
+        self.exec_global_asm()?;
+        self.call_decl(decl, args, &decl.span)
+    }
+
+    /// The memory of the program, to follow the pointers it returns
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// The memory of the program, to allocate what pointer arguments point to
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    fn collect_funcs(&mut self, decl: &'a ConstDecl) {
+        match &decl.init {
+            ConstInitVal::Function(func) => {
+                self.funcs.insert(self.ids.const_decl(decl).unwrap(), decl);
+                self.collect_block(&func.block);
+            }
+            ConstInitVal::Proto(_) => {
+                self.funcs.insert(self.ids.const_decl(decl).unwrap(), decl);
+            }
+            ConstInitVal::Class(_) | ConstInitVal::Exp(_) => {}
+        }
+    }
+
+    fn collect_block(&mut self, block: &'a Block) {
+        for item in block.items.iter() {
+            match item {
+                BlockItem::Decl(Decl::Const(decl)) => self.collect_funcs(decl),
+                BlockItem::Decl(Decl::Var(_)) => {}
+                BlockItem::Stmt(stmt) => match stmt {
+                    Stmt::Block(block) => self.collect_block(block),
+                    Stmt::If(if_stmt) => {
+                        self.collect_block(&if_stmt.then);
+                        if let Some(else_then) = &if_stmt.else_then {
+                            self.collect_block(else_then);
+                        }
+                    }
+                    Stmt::While(while_stmt) => self.collect_block(&while_stmt.then),
+                    Stmt::For(for_stmt) => self.collect_block(&for_stmt.then),
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn const_symbol(&self, decl: &ConstDecl) -> SymbolId {
+        let id = self.ids.const_decl(decl).unwrap();
+        self.defs[&SymbolKind::Const(id)]
+    }
+
+    /// The symbol `lval` refers to
+    pub(crate) fn lval_symbol(&self, lval: &LVal) -> SymbolId {
+        self.resolution.uses[self.ids.lval(lval).unwrap()]
+    }
+
+    pub(crate) fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    pub(crate) fn pop_frame(&mut self) {
+        let frame = self.frames.pop().unwrap();
+        if frame.region == Region::Stack {
+            for ptr in frame.locals.into_values().chain(frame.arrays.into_values()) {
+                self.memory.release(ptr);
+            }
+        }
+    }
+
+    /// Give the local `symbol` the value `value`, reusing its storage if it is still live,
+    /// as the variable of a `for` is in the next iteration
+    fn declare(&mut self, symbol: SymbolId, value: Value, span: &Span) -> Result<(), Error> {
+        match self.frame().locals.get(&symbol).copied() {
+            Some(ptr) => self.write(ptr, value, span),
+            None => {
+                let region = self.frame().region;
+                let ptr = self.alloc(region, vec![value], span)?;
+                self.frame().locals.insert(symbol, ptr);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn alloc(
+        &mut self,
+        region: Region,
+        values: Vec<Value>,
+        span: &Span,
+    ) -> Result<Pointer, Error> {
+        self.memory
+            .alloc(region, values)
+            .map_err(|kind| Error::new(kind, span.clone()))
+    }
+
+    pub(crate) fn call_decl(
+        &mut self,
+        decl: &'a ConstDecl,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Result<Value, Error> {
+        let func = match &decl.init {
+            ConstInitVal::Function(func) => func,
+            ConstInitVal::Proto(proto) => return self.call_host(decl, proto, args, span),
+            _ => return Err(not_a_function(span)),
+        };
+
+        if let Some(max_depth) = self.limits.max_depth {
+            if self.depth >= max_depth {
+                return Err(Error::new(ErrorKind::CallDepth(max_depth), span.clone()));
+            }
+        }
+
+        let id = self.ids.const_decl(decl).unwrap();
+        self.depth += 1;
+        self.frames.push(Frame::new(Region::Stack));
+        let flow = self.run_func(id, func, args, span);
+        self.pop_frame();
+        self.depth -= 1;
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            Flow::Next if func.func_type.is_void() => Ok(Value::Void),
+            Flow::Next => Err(Error::new(
+                ErrorKind::MissingReturn(decl.id.clone()),
+                func.span.clone(),
+            )),
+            Flow::Break(span) | Flow::Continue(span) => {
+                Err(Error::new(ErrorKind::TerminatorOutsideLoop, span))
+            }
+        }
+    }
+
+    fn run_func(
+        &mut self,
+        id: NodeId,
+        func: &'a FuncDef,
+        args: Vec<Value>,
+        span: &Span,
+    ) -> Result<Flow, Error> {
+        for (index, arg) in args.into_iter().enumerate() {
+            if let Some(symbol) = self.defs.get(&SymbolKind::Param { func: id, index }) {
+                self.declare(*symbol, arg, span)?;
+            }
+        }
+        self.exec_block(&func.block)
+    }
+
+    pub(crate) fn read(&self, ptr: Pointer, span: &Span) -> Result<Value, Error> {
+        self.memory
+            .read(ptr)
+            .map_err(|kind| Error::new(kind, span.clone()))
+    }
+
+    pub(crate) fn write(&mut self, ptr: Pointer, value: Value, span: &Span) -> Result<(), Error> {
+        self.memory
+            .write(ptr, value)
+            .map_err(|kind| Error::new(kind, span.clone()))
+    }
+
+    /// Where the value `lval` names is stored
+    pub(crate) fn place(&mut self, lval: &'a LVal) -> Result<Pointer, Error> {
+        if lval.ids.len() > 1 {
+            return Err(unsupported("class members", &lval.span));
+        }
+        let symbol = self.lval_symbol(lval);
+        let ptr = match self.frame().locals.get(&symbol) {
+            Some(ptr) => *ptr,
+            None => self.global(symbol, &lval.span)?,
+        };
+        match &lval.exp {
+            Some(index) => {
+                let base = self.read(ptr, &lval.span)?;
+                self.index(base, index, &lval.span)
+            }
+            None => Ok(ptr),
+        }
+    }
+
+    /// Where the value `deref` points to is stored
+    pub(crate) fn deref_place(&mut self, deref: &'a Deref) -> Result<Pointer, Error> {
+        match deref {
+            Deref::DerefId(lval, span) => {
+                let ptr = self.place(lval)?;
+                as_pointer(self.read(ptr, span)?, span)
+            }
+            Deref::DerefExp(exp, span) => as_pointer(self.eval(exp)?, span),
+            Deref::DerefPtr(lval, index, span) => {
+                let ptr = self.place(lval)?;
+                let base = self.read(ptr, span)?;
+                self.index(base, index, span)
+            }
+            Deref::DerefPtrExp(exp, index, span) => {
+                let base = self.eval(exp)?;
+                self.index(base, index, span)
+            }
+        }
+    }
+
+    fn index(&mut self, base: Value, index: &'a Exp, span: &Span) -> Result<Pointer, Error> {
+        let Value::Int(num, _) = self.eval(index)? else {
+            return Err(internal(
+                "through an index that isn't an integer",
+                &index.full_span(),
+            ));
+        };
+        Ok(as_pointer(base, span)?.offset(to_offset(&num)))
+    }
+
+    /// The storage of the global `symbol`, evaluating it if it wasn't yet
+    fn global(&mut self, symbol: SymbolId, span: &Span) -> Result<Pointer, Error> {
+        let exp = match self.globals.get(&symbol) {
+            Some(Global::Done(ptr)) => return Ok(*ptr),
+            Some(Global::Pending(exp)) => *exp,
+            Some(Global::Evaluating) => {
+                let name = self.resolution.symbol(symbol).name.clone();
+                return Err(Error::new(ErrorKind::CyclicConstant(name), span.clone()));
+            }
+            None => return Err(self.capture(symbol, span)),
+        };
+
+        self.globals.insert(symbol, Global::Evaluating);
+        self.frames.push(Frame::new(Region::Global));
+        let value = self.eval(exp);
+        self.pop_frame();
+
+        match value.and_then(|value| self.alloc(Region::Global, vec![value], span)) {
+            Ok(ptr) => {
+                self.globals.insert(symbol, Global::Done(ptr));
+                Ok(ptr)
+            }
+            Err(err) => {
+                self.globals.insert(symbol, Global::Pending(exp));
+                Err(err)
+            }
+        }
+    }
+
+    fn capture(&self, symbol: SymbolId, span: &Span) -> Error {
+        let name = self.resolution.symbol(symbol).name.clone();
+        Error::new(ErrorKind::Capture(name), span.clone())
+    }
+
+    pub(crate) fn exec_block(&mut self, block: &'a Block) -> Result<Flow, Error> {
+        let flow = grow_stack(|| self.exec_items(block));
+        for item in block.items.iter() {
+            if let BlockItem::Decl(decl) = item {
+                if let Some(symbol) = self.decl_symbol(decl) {
+                    self.free_local(symbol);
+                }
+            }
+        }
+        flow
+    }
+
+    /// Free the storage of the local `symbol` when its scope ends, so pointers to it
+    /// dangle. Locals of a global being evaluated live as long as the global.
+    fn free_local(&mut self, symbol: SymbolId) {
+        if self.frame().region != Region::Stack {
+            return;
+        }
+        if let Some(ptr) = self.frame().locals.remove(&symbol) {
+            self.memory.release(ptr);
+        }
+    }
+
+    /// The local `decl` declares, functions aren't stored in one
+    fn decl_symbol(&self, decl: &'a Decl) -> Option<SymbolId> {
+        match decl {
+            Decl::Var(_) => {
+                let id = self.ids.decl(decl).unwrap();
+                Some(self.defs[&SymbolKind::Var(id)])
+            }
+            Decl::Const(decl) => match &decl.init {
+                ConstInitVal::Exp(_) => Some(self.const_symbol(decl)),
+                _ => None,
+            },
+        }
+    }
+
+    fn exec_items(&mut self, block: &'a Block) -> Result<Flow, Error> {
+        for item in block.items.iter() {
+            let flow = match item {
+                BlockItem::Decl(decl) => {
+                    self.exec_decl(decl)?;
+                    Flow::Next
+                }
+                BlockItem::Stmt(stmt) => self.exec_stmt(stmt)?,
+            };
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_decl(&mut self, decl: &'a Decl) -> Result<(), Error> {
+        let (exp, span) = match decl {
+            Decl::Var(var) => (&var.init.exp, &var.span),
+            Decl::Const(decl) => match &decl.init {
+                ConstInitVal::Exp(exp) => (&exp.exp, &decl.span),
+                // Functions are collected up front
+                _ => return Ok(()),
+            },
+        };
+        let symbol = self.decl_symbol(decl).unwrap();
+        let value = self.eval(exp)?;
+        self.declare(symbol, value, span)
+    }
+
+    fn exec_stmt(&mut self, stmt: &'a Stmt) -> Result<Flow, Error> {
+        self.step(|| stmt.span().unwrap_or_else(Span::dummy))?;
+        match stmt {
+            Stmt::Assign(Assign::WriteVar(lval, exp, _)) => {
+                let value = self.eval(exp)?;
+                let ptr = self.place(lval)?;
+                self.write(ptr, value, &lval.span)?;
+            }
+            Stmt::Assign(Assign::WritePtr(deref, exp, _)) => {
+                let value = self.eval(exp)?;
+                let ptr = self.deref_place(deref)?;
+                self.write(ptr, value, &deref.get_span())?;
+            }
+            Stmt::Return(ret) => {
+                let value = match &ret.exp {
+                    Some(exp) => self.eval(exp)?,
+                    None => Value::Void,
+                };
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Block(block) => return self.exec_block(block),
+            Stmt::Exp(Some(exp)) => {
+                self.eval(exp)?;
+            }
+            Stmt::Exp(None) => {}
+            Stmt::If(if_stmt) => {
+                if self.eval(&if_stmt.cond)?.is_true() {
+                    return self.exec_block(&if_stmt.then);
+                }
+                if let Some(else_then) = &if_stmt.else_then {
+                    return self.exec_block(else_then);
+                }
+            }
+            Stmt::InlineAsm(asm) => self.exec_asm(asm)?,
+            Stmt::While(while_stmt) => {
+                while self.eval(&while_stmt.cond)?.is_true() {
+                    match self.exec_block(&while_stmt.then)? {
+                        Flow::Break(_) => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue(_) => {}
+                    }
+                }
+            }
+            Stmt::Terminator(Terminator::Break(span)) => return Ok(Flow::Break(span.clone())),
+            Stmt::Terminator(Terminator::Continue(span)) => {
+                return Ok(Flow::Continue(span.clone()))
+            }
+            Stmt::For(for_stmt) => return self.exec_for(stmt, for_stmt),
+        }
+        Ok(Flow::Next)
+    }
+
+    /// Run the body with the loop variable going from `start` towards `end`, which it
+    /// never reaches, by `step`. Each step adds to the current value of the variable, so
+    /// the body may change it.
+    fn exec_for(&mut self, stmt: &'a Stmt, for_stmt: &'a For) -> Result<Flow, Error> {
+        let (Value::Int(start, ty), Value::Int(end, _), Value::Int(step, _)) = (
+            self.eval(&for_stmt.start)?,
+            self.eval(&for_stmt.end)?,
+            self.eval(&for_stmt.step)?,
+        ) else {
+            return Err(internal(
+                "through loop bounds that aren't integers",
+                &for_stmt.span,
+            ));
+        };
+        if step == BigInt::from(0) {
+            return Err(Error::new(ErrorKind::ZeroStep, for_stmt.step.full_span()));
+        }
+
+        let symbol = self.defs[&SymbolKind::ForVar(self.ids.stmt(stmt).unwrap())];
+        let flow = self.exec_loop(for_stmt, symbol, start, ty, end, step);
+        // The loop variable is only in scope in the loop
+        self.free_local(symbol);
+        flow
+    }
+
+    fn exec_loop(
+        &mut self,
+        for_stmt: &'a For,
+        symbol: SymbolId,
+        mut num: BigInt,
+        ty: IntType,
+        end: BigInt,
+        step: BigInt,
+    ) -> Result<Flow, Error> {
+        let forward = step > BigInt::from(0);
+        // `num` isn't wrapped around, so a step past the end of the type ends the loop
+        while (forward && num < end) || (!forward && num > end) {
+            self.step(|| for_stmt.span.clone())?;
+            self.declare(symbol, Value::Int(num, ty), &for_stmt.span)?;
+            match self.exec_block(&for_stmt.then)? {
+                Flow::Break(_) => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Next | Flow::Continue(_) => {}
+            }
+            let ptr = self.frame().locals[&symbol];
+            let Value::Int(current, _) = self.read(ptr, &for_stmt.span)? else {
+                return Err(internal("the loop variable change type", &for_stmt.span));
+            };
+            num = current + &step;
+        }
+        Ok(Flow::Next)
+    }
+}
+
+pub(crate) fn as_pointer(value: Value, span: &Span) -> Result<Pointer, Error> {
+    match value {
+        Value::Ptr(ptr) => Ok(ptr),
+        value => Err(internal(&format!("through dereferencing {}", value), span)),
+    }
+}
+
+/// `num` as a pointer offset, offsets too large for an `i64` are out of bounds anyway
+pub(crate) fn to_offset(num: &BigInt) -> i64 {
+    i64::try_from(num).unwrap_or(if num.sign() == Sign::Minus {
+        i64::MIN
+    } else {
+        i64::MAX
+    })
+}
+
+pub(crate) fn unsupported(what: &str, span: &Span) -> Error {
+    Error::new(ErrorKind::Unsupported(what.to_string()), span.clone())
+}
+
+pub(crate) fn internal(what: &str, span: &Span) -> Error {
+    Error::new(ErrorKind::Internal(what.to_string()), span.clone())
+}
+
+pub(crate) fn not_a_function(span: &Span) -> Error {
+    internal("through a call of something that isn't a function", span)
+}
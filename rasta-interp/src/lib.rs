@@ -0,0 +1,36 @@
+//! A tree-walking interpreter for rasta ASTs, to run programs without a backend.
+//!
+//! ```
+//! use rasta::build::*;
+//! use rasta_interp::{Interpreter, Value};
+//!
+//! let add = func("add", u8())
+//!     .param("a", u8())
+//!     .param("b", u8())
+//!     .ret(rasta::build::add(var("a"), var("b")));
+//! let unit = unit([add.into()]);
+//!
+//! let mut interp = Interpreter::new(&unit).unwrap();
+//! let sum = interp.call("add", vec![Value::from(200u8), Value::from(100u8)]);
+//! // Integers wrap around like the hardware they describe
+//! assert_eq!(sum.unwrap(), Value::from(44u8));
+//! ```
+
+mod asm;
+mod error;
+mod eval;
+mod host;
+mod interp;
+mod limits;
+mod memory;
+mod value;
+
+pub use asm::*;
+pub use error::*;
+pub use host::*;
+pub use interp::*;
+pub use limits::*;
+pub use memory::*;
+pub use value::*;
+
+use rasta::*;
@@ -0,0 +1,65 @@
+use std::time::Instant;
+
+use super::*;
+
+/// How many steps run between two looks at the clock
+const DEADLINE_INTERVAL: u64 = 1024;
+
+/// The stack left when a new segment is allocated, and the size of the segments
+const RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+/// Bounds on what a program may use, to run untrusted ones
+///
+/// Every limit is off by default. The interpreter grows its stack as programs recurse, so
+/// without `max_depth` deep recursion only runs out of memory.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// The number of steps a program may take, every expression evaluated, statement
+    /// executed and loop iteration is one
+    pub fuel: Option<u64>,
+    /// How deep calls may nest
+    pub max_depth: Option<usize>,
+    /// How many bytes memory may hold at once, with every value as large as in C, which
+    /// integers being computed must fit in too
+    pub max_memory: Option<usize>,
+    /// When to stop running
+    pub deadline: Option<Instant>,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Limit what the program may use from now on, giving it `limits.fuel` steps
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.fuel = limits.fuel;
+        self.memory.set_limit(limits.max_memory);
+        self.limits = limits;
+    }
+
+    /// The steps the program may still take, `None` if they're unlimited
+    pub fn remaining_fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Take a step, failing at `span` if that's one too many
+    pub(crate) fn step(&mut self, span: impl FnOnce() -> Span) -> Result<(), Error> {
+        self.steps += 1;
+        if let Some(fuel) = &mut self.fuel {
+            if *fuel == 0 {
+                return Err(Error::new(ErrorKind::OutOfFuel, span()));
+            }
+            *fuel -= 1;
+        }
+        if let Some(deadline) = self.limits.deadline {
+            if self.steps.is_multiple_of(DEADLINE_INTERVAL) && Instant::now() >= deadline {
+                return Err(Error::new(ErrorKind::Deadline, span()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run `f`, on a new stack segment if the current one is almost full, so deep recursion
+/// of the interpreted program doesn't overflow the stack
+pub(crate) fn grow_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, f)
+}
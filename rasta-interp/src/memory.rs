@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::*;
+
+/// Where an allocation lives, which decides how long it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// Locals and arrays of a running function, freed when it returns
+    Stack,
+    /// Global constants and string literals, never freed
+    Global,
+    /// Allocated and freed by the embedder
+    Heap,
+}
+
+/// The address of an element of an allocation
+///
+/// Pointers may point anywhere past the bounds of their allocation, or to one that was
+/// freed, that is only checked when they are read or written through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pointer {
+    alloc: usize,
+    index: i64,
+}
+
+impl Pointer {
+    /// The pointer `count` elements further, like `p + count`
+    pub fn offset(self, count: i64) -> Self {
+        Self {
+            alloc: self.alloc,
+            index: self.index.saturating_add(count),
+        }
+    }
+
+    /// The index of the element pointed to in its allocation
+    pub fn index(self) -> i64 {
+        self.index
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<pointer {}:{}>", self.alloc, self.index)
+    }
+}
+
+struct Allocation {
+    region: Region,
+    values: Vec<Value>,
+}
+
+/// The memory of the interpreted program
+///
+/// Memory is addressed in values, not bytes: every element of an allocation holds one
+/// value whatever its type, and pointer arithmetic moves by elements. Its size is counted
+/// in the bytes the values would take in C though. Freed allocations are forgotten, so
+/// every pointer into them dangles.
+#[derive(Default)]
+pub struct Memory {
+    allocs: HashMap<usize, Allocation>,
+    next: usize,
+    /// The size of the values of all allocations
+    used: usize,
+    limit: Option<usize>,
+}
+
+impl Memory {
+    /// Allocate room for `values` in `region`, returning a pointer to the first one
+    pub fn alloc(&mut self, region: Region, values: Vec<Value>) -> Result<Pointer, ErrorKind> {
+        let size = size_of(&values);
+        self.reserve(size)?;
+        let alloc = self.next;
+        self.next += 1;
+        self.used += size;
+        self.allocs.insert(alloc, Allocation { region, values });
+        Ok(Pointer { alloc, index: 0 })
+    }
+
+    /// Check that `size` more bytes fit in memory, before making room for them
+    pub(crate) fn reserve(&self, size: usize) -> Result<(), ErrorKind> {
+        match self.limit {
+            Some(limit) if self.used.saturating_add(size) > limit => {
+                Err(ErrorKind::OutOfMemory(limit))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// The number of bytes memory holds
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Free the heap allocation `ptr` points to the start of
+    pub fn free(&mut self, ptr: Pointer) -> Result<(), ErrorKind> {
+        match self.allocs.get(&ptr.alloc) {
+            None => Err(ErrorKind::Dangling),
+            Some(alloc) if alloc.region != Region::Heap || ptr.index != 0 => {
+                Err(ErrorKind::InvalidFree)
+            }
+            Some(_) => {
+                self.release(ptr);
+                Ok(())
+            }
+        }
+    }
+
+    /// Free the allocation of `ptr` whatever its region, for frames that return
+    pub(crate) fn release(&mut self, ptr: Pointer) {
+        if let Some(alloc) = self.allocs.remove(&ptr.alloc) {
+            self.used -= size_of(&alloc.values);
+        }
+    }
+
+    /// The region of the allocation `ptr` points into, `None` if it was freed
+    pub fn region(&self, ptr: Pointer) -> Option<Region> {
+        self.allocs.get(&ptr.alloc).map(|alloc| alloc.region)
+    }
+
+    /// The number of elements of the allocation `ptr` points into, `None` if it was freed
+    pub fn len(&self, ptr: Pointer) -> Option<usize> {
+        self.allocs.get(&ptr.alloc).map(|alloc| alloc.values.len())
+    }
+
+    pub fn read(&self, ptr: Pointer) -> Result<Value, ErrorKind> {
+        let alloc = self.allocs.get(&ptr.alloc).ok_or(ErrorKind::Dangling)?;
+        let index = checked_index(ptr, alloc.values.len())?;
+        Ok(alloc.values[index].clone())
+    }
+
+    pub fn write(&mut self, ptr: Pointer, value: Value) -> Result<(), ErrorKind> {
+        let alloc = self.allocs.get(&ptr.alloc).ok_or(ErrorKind::Dangling)?;
+        let index = checked_index(ptr, alloc.values.len())?;
+        let old = alloc.values[index].size();
+        self.reserve(value.size().saturating_sub(old))?;
+        self.used = self.used - old + value.size();
+        self.allocs.get_mut(&ptr.alloc).unwrap().values[index] = value;
+        Ok(())
+    }
+
+    /// Read the integers from `ptr` up to a zero as bytes of UTF-8, like C strings, failing
+    /// on anything else
+    pub fn read_str(&self, mut ptr: Pointer) -> Result<String, ErrorKind> {
+        let mut bytes = Vec::new();
+        loop {
+            let num = match self.read(ptr)? {
+                Value::Int(num, _) => num,
+                found => {
+                    return Err(ErrorKind::TypeMismatch {
+                        expected: "a character".to_string(),
+                        found,
+                    })
+                }
+            };
+            // The low byte, as two's complement for negative `i8`s
+            match u8::try_from(num & BigInt::from(0xff)).unwrap() {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+            ptr = ptr.offset(1);
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+fn size_of(values: &[Value]) -> usize {
+    values.iter().map(Value::size).sum()
+}
+
+fn checked_index(ptr: Pointer, len: usize) -> Result<usize, ErrorKind> {
+    usize::try_from(ptr.index)
+        .ok()
+        .filter(|index| *index < len)
+        .ok_or(ErrorKind::OutOfBounds {
+            index: ptr.index,
+            len,
+        })
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use rasta::num_bigint::Sign;
+
+use super::*;
+
+/// The signedness and width of an integer type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntType {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+impl IntType {
+    /// The integer type `ty` stands for, `None` for `bool` and non-integers
+    pub fn of(ty: &VTypeEnum) -> Option<Self> {
+        match ty {
+            VTypeEnum::Bool => None,
+            ty => Some(Self {
+                signed: ty.is_signed(),
+                bits: ty.bit_width()?,
+            }),
+        }
+    }
+
+    /// The number of bytes a value of the type takes, as in C
+    pub fn size(self) -> usize {
+        (self.bits as usize).div_ceil(8)
+    }
+
+    /// Wrap `num` around into the range of the type, like two's complement hardware does
+    pub fn wrap(self, num: BigInt) -> BigInt {
+        // Most numbers are in range already, which is known without computing the bounds
+        let fits = match num.sign() {
+            _ if self.signed => num.bits() < u64::from(self.bits),
+            Sign::Minus => false,
+            _ => num.bits() <= u64::from(self.bits),
+        };
+        if fits {
+            return num;
+        }
+        let modulus = BigInt::from(1) << self.bits;
+        let mut num = num % &modulus;
+        if num.sign() == Sign::Minus {
+            num += &modulus;
+        }
+        if self.signed && num >= &modulus >> 1 {
+            num -= modulus;
+        }
+        num
+    }
+}
+
+/// A value of the interpreted program
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// What `void` functions return
+    Void,
+    Bool(bool),
+    /// An integer, always in the range of its type
+    Int(BigInt, IntType),
+    F32(f32),
+    F64(f64),
+    Ptr(Pointer),
+}
+
+impl Value {
+    /// The integer `num` of type `ty`, wrapped around into its range
+    pub fn int(num: impl Into<BigInt>, ty: IntType) -> Self {
+        Self::Int(ty.wrap(num.into()), ty)
+    }
+
+    /// The number of bytes the value takes, as in C
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Void => 0,
+            Self::Bool(_) => 1,
+            Self::Int(_, ty) => ty.size(),
+            Self::F32(_) => 4,
+            Self::F64(_) | Self::Ptr(_) => 8,
+        }
+    }
+
+    /// Whether the value holds as a condition, `true` and integers other than zero do
+    pub fn is_true(&self) -> bool {
+        match self {
+            Self::Bool(value) => *value,
+            Self::Int(num, _) => num.sign() != Sign::NoSign,
+            _ => false,
+        }
+    }
+
+    /// Whether both values are of the same type, any two pointers are
+    pub fn same_type(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Self::Int(_, ty), Self::Int(_, other)) => ty == other,
+            (lhs, rhs) => std::mem::discriminant(lhs) == std::mem::discriminant(rhs),
+        }
+    }
+
+    /// Whether the value is of type `ty`
+    pub fn has_type(&self, ty: &VType) -> bool {
+        match self {
+            Self::Void => ty.is_void(),
+            Self::Bool(_) => ty.is_bool(),
+            Self::Int(_, int) => !ty.is_pointer() && IntType::of(&ty.ty) == Some(*int),
+            Self::F32(_) => !ty.is_pointer() && ty.ty == VTypeEnum::F32,
+            Self::F64(_) => !ty.is_pointer() && ty.ty == VTypeEnum::F64,
+            Self::Ptr(_) => ty.is_pointer(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Void => write!(f, "void"),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Int(num, _) => write!(f, "{}", num),
+            Self::F32(num) => write!(f, "{:?}", num),
+            Self::F64(num) => write!(f, "{:?}", num),
+            Self::Ptr(ptr) => ptr.fmt(f),
+        }
+    }
+}
+
+impl From<Pointer> for Value {
+    fn from(ptr: Pointer) -> Self {
+        Self::Ptr(ptr)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::F32(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+macro_rules! int_from {
+    ($($ty:ty: $signed:expr, $bits:expr;)*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    let ty = IntType {
+                        signed: $signed,
+                        bits: $bits,
+                    };
+                    Self::Int(BigInt::from(value), ty)
+                }
+            }
+        )*
+    };
+}
+
+int_from! {
+    u8: false, 8;
+    u16: false, 16;
+    u32: false, 32;
+    u64: false, 64;
+    u128: false, 128;
+    i8: true, 8;
+    i16: true, 16;
+    i32: true, 32;
+    i64: true, 64;
+    i128: true, 128;
+}
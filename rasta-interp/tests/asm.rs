@@ -0,0 +1,138 @@
+use rasta::CompUnit;
+use rasta_interp::{Error, ErrorKind, Interpreter, Memory, Value};
+
+const SOURCE: &str = "
+const add = fn (arg x: u64, arg y: u64) -> u64 {
+    var sum: u64 = 0;
+    var carry: bool = x == y;
+    asm(\"add\", in a = x, in b = y, out c = sum, out d = carry);
+    return sum;
+};
+";
+
+fn unit(source: &str) -> CompUnit {
+    cara::parse(source.to_string(), "test.cara".to_string()).unwrap()
+}
+
+/// Call `add` with `handler` running its assembly
+fn add<H>(unit: &CompUnit, handler: H) -> Result<Value, Error>
+where
+    H: FnMut(&str, &[(&str, Value)], &[(&str, Value)], &mut Memory) -> Result<Vec<Value>, String>,
+{
+    let mut interp = Interpreter::new(unit).unwrap();
+    interp.set_asm_handler(handler);
+    interp.call("add", vec![Value::from(40u64), Value::from(2u64)])
+}
+
+/// The error kind and the source text its span covers
+fn located(unit: &CompUnit, err: Error) -> (ErrorKind, String) {
+    let text = err.span.text(&unit.source_map).unwrap_or("");
+    (err.kind, text.to_string())
+}
+
+const BLOCK: &str = "asm(\"add\", in a = x, in b = y, out c = sum, out d = carry);";
+
+#[test]
+fn outputs_are_written_back() {
+    let unit = unit(SOURCE);
+    let result = add(&unit, |asm, inputs, outputs, _| {
+        assert_eq!(asm, "add");
+        assert_eq!(
+            inputs,
+            [("a", Value::from(40u64)), ("b", Value::from(2u64))]
+        );
+        assert_eq!(
+            outputs,
+            [("c", Value::from(0u64)), ("d", Value::Bool(false))]
+        );
+        Ok(vec![Value::from(42u64), Value::Bool(false)])
+    });
+    assert_eq!(result.unwrap(), Value::from(42u64));
+}
+
+#[test]
+fn without_a_handler() {
+    let unit = unit(SOURCE);
+    let mut interp = Interpreter::new(&unit).unwrap();
+    let err = interp
+        .call("add", vec![Value::from(1u64), Value::from(2u64)])
+        .unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (ErrorKind::NoAsmHandler, BLOCK.to_string())
+    );
+}
+
+#[test]
+fn failing_handler() {
+    let unit = unit(SOURCE);
+    let err = add(&unit, |asm, _, _, _| Err(format!("unknown `{}`", asm))).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (
+            ErrorKind::Asm("unknown `add`".to_string()),
+            BLOCK.to_string()
+        )
+    );
+}
+
+#[test]
+fn wrong_number_of_outputs() {
+    let unit = unit(SOURCE);
+    for values in [vec![], vec![Value::from(42u64)]] {
+        let found = values.len();
+        let err = add(&unit, move |_, _, _, _| Ok(values.clone())).unwrap_err();
+        assert_eq!(
+            located(&unit, err),
+            (
+                ErrorKind::AsmOutputCount { expected: 2, found },
+                BLOCK.to_string()
+            )
+        );
+    }
+}
+
+#[test]
+fn wrong_output_types() {
+    let unit = unit(SOURCE);
+    let err = add(&unit, |_, _, _, _| {
+        Ok(vec![Value::from(42u32), Value::Bool(true)])
+    })
+    .unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (
+            ErrorKind::AsmOutputType("c".to_string()),
+            "out c = sum".to_string()
+        )
+    );
+
+    let err = add(&unit, |_, _, _, _| {
+        Ok(vec![Value::from(42u64), Value::from(1u64)])
+    })
+    .unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (
+            ErrorKind::AsmOutputType("d".to_string()),
+            "out d = carry".to_string()
+        )
+    );
+}
+
+#[test]
+fn global_blocks_run_before_the_first_call() {
+    let unit = unit("asm(\"init\"); const main = fn () -> u64 { return 1; };");
+    let mut ran = Vec::new();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.set_asm_handler(
+        |asm: &str, _: &[(&str, Value)], _: &[(&str, Value)], _: &mut Memory| {
+            ran.push(asm.to_string());
+            Ok(Vec::new())
+        },
+    );
+    interp.call("main", Vec::new()).unwrap();
+    interp.call("main", Vec::new()).unwrap();
+    drop(interp);
+    assert_eq!(ran, ["init"]);
+}
@@ -0,0 +1,172 @@
+use rasta::CompUnit;
+use rasta_interp::{Error, ErrorKind, Interpreter, Value};
+
+const SOURCE: &str = "
+const mul = fn (arg a: u64, arg b: u64) -> u64;
+const putc = fn (arg c: i8) -> void;
+const tick = fn () -> u64;
+const main = fn () -> u64 { putc(1); return mul(tick(), 3); };
+";
+
+fn unit() -> CompUnit {
+    cara::parse(SOURCE.to_string(), "test.cara".to_string()).unwrap()
+}
+
+/// The error kind and the source text its span covers
+fn located(unit: &CompUnit, err: Error) -> (ErrorKind, String) {
+    let text = err.span.text(&unit.source_map).unwrap_or("");
+    (err.kind, text.trim_end().to_string())
+}
+
+fn arity(name: &str, params: usize) -> ErrorKind {
+    ErrorKind::HostArity {
+        name: name.to_string(),
+        params,
+    }
+}
+
+fn host_type(name: &str, ty: &str) -> ErrorKind {
+    ErrorKind::HostType {
+        name: name.to_string(),
+        ty: ty.to_string(),
+    }
+}
+
+#[test]
+fn calls_registered_functions() {
+    let unit = unit();
+    let mut printed = Vec::new();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.register("mul", |a: u64, b: u64| a * b).unwrap();
+    interp.register("putc", |c: i8| printed.push(c)).unwrap();
+    interp.register("tick", || 14u64).unwrap();
+    assert_eq!(interp.call("main", Vec::new()).unwrap(), Value::from(42u64));
+    drop(interp);
+    assert_eq!(printed, [1]);
+}
+
+#[test]
+fn wrong_arity() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+
+    let err = interp.register("mul", |a: u64| a).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (arity("mul", 1), "arg a: u64, arg b: u64".to_string())
+    );
+
+    let err = interp.register("putc", || ()).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (arity("putc", 0), "arg c: i8".to_string())
+    );
+
+    // Without parameters the whole declaration is pointed at
+    let err = interp.register("tick", |a: u64| a).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (arity("tick", 1), "const tick = fn () -> u64;".to_string())
+    );
+}
+
+#[test]
+fn wrong_types() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+
+    let err = interp
+        .register("mul", |a: u64, b: i64| a as i64 * b)
+        .unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (host_type("mul", "u64"), "arg b: u64".to_string())
+    );
+
+    let err = interp
+        .register("putc", |c: String| c.is_empty())
+        .unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (host_type("putc", "i8"), "arg c: i8".to_string())
+    );
+
+    let err = interp.register("putc", |c: i8| c).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (host_type("putc", "void"), "void".to_string())
+    );
+
+    let err = interp.register("tick", || 1u32).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (host_type("tick", "u64"), "u64".to_string())
+    );
+}
+
+#[test]
+fn only_functions_without_a_body() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    let err = interp.register("main", || 1u64).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::NotAProto("main".to_string()));
+    let err = interp
+        .register_raw("exit", |_, _| Ok(Value::Void))
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::NotAProto("exit".to_string()));
+}
+
+#[test]
+fn unregistered_function() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    let err = interp.call("main", Vec::new()).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (ErrorKind::NoBody("putc".to_string()), "putc(1)".to_string())
+    );
+}
+
+#[test]
+fn failing_host_function() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.register("putc", |_: i8| ()).unwrap();
+    interp
+        .register("tick", || Err::<u64, _>("no clock"))
+        .unwrap();
+    let err = interp.call("main", Vec::new()).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (
+            ErrorKind::Host("no clock".to_string()),
+            "tick()".to_string()
+        )
+    );
+}
+
+#[test]
+fn raw_host_function_returning_the_wrong_type() {
+    let unit = unit();
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp
+        .register_raw("putc", |_, args| match args[..] {
+            [Value::Int(..)] => Ok(Value::Void),
+            _ => Err(format!("bad arguments {:?}", args)),
+        })
+        .unwrap();
+    interp
+        .register_raw("tick", |_, _| Ok(Value::from(1u32)))
+        .unwrap();
+    let err = interp.call("main", Vec::new()).unwrap_err();
+    assert_eq!(
+        located(&unit, err),
+        (
+            ErrorKind::HostReturn {
+                name: "tick".to_string(),
+                expected: "u64".to_string(),
+            },
+            "tick()".to_string()
+        )
+    );
+}
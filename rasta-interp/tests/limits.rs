@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use rasta::CompUnit;
+use rasta_interp::{Error, ErrorKind, Interpreter, Limits, Value};
+
+fn parse(source: &str) -> CompUnit {
+    cara::parse(source.to_string(), "test.cara".to_string()).unwrap()
+}
+
+/// Call `main` of `source` within `limits`
+fn run(source: &str, limits: Limits) -> Result<Value, Error> {
+    let unit = parse(source);
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.set_limits(limits);
+    interp.call("main", Vec::new())
+}
+
+const FOREVER: &str = "
+    const main = fn () -> u64 {
+        while 1 {
+        }
+        return 0;
+    };
+";
+
+const RECURSE: &str = "
+    const down = fn (arg n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        return down(n - 1) + 1;
+    };
+    const main = fn () -> u64 {
+        return down(1000);
+    };
+";
+
+#[test]
+fn fuel() {
+    let limits = Limits {
+        fuel: Some(1000),
+        ..Limits::default()
+    };
+    assert_eq!(run(FOREVER, limits).unwrap_err().kind, ErrorKind::OutOfFuel);
+}
+
+#[test]
+fn remaining_fuel() {
+    let unit = parse("const main = fn () -> u64 { return 1 + 2; };");
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.set_limits(Limits {
+        fuel: Some(100),
+        ..Limits::default()
+    });
+    assert_eq!(interp.call("main", Vec::new()).unwrap(), Value::from(3u64));
+    // The `return` and its three expressions
+    assert_eq!(interp.remaining_fuel(), Some(96));
+}
+
+#[test]
+fn max_depth() {
+    let limits = Limits {
+        max_depth: Some(100),
+        ..Limits::default()
+    };
+    assert_eq!(
+        run(RECURSE, limits).unwrap_err().kind,
+        ErrorKind::CallDepth(100)
+    );
+
+    // `main` and the 1001 calls of `down`
+    let limits = Limits {
+        max_depth: Some(1002),
+        ..Limits::default()
+    };
+    assert_eq!(run(RECURSE, limits).unwrap(), Value::from(1000u64));
+}
+
+#[test]
+fn max_memory() {
+    let source = "
+        const main = fn () -> u64 {
+            var a: u64* = [0; 100];
+            return a[99];
+        };
+    ";
+    let limits = Limits {
+        max_memory: Some(800),
+        ..Limits::default()
+    };
+    assert_eq!(
+        run(source, limits).unwrap_err().kind,
+        ErrorKind::OutOfMemory(800)
+    );
+
+    // The array and the pointer to it
+    let limits = Limits {
+        max_memory: Some(808),
+        ..Limits::default()
+    };
+    assert_eq!(run(source, limits).unwrap(), Value::from(0u64));
+}
+
+#[test]
+fn max_memory_of_wide_integers() {
+    let source = "
+        const main = fn () -> u64 {
+            var x: u4000000000 = 1;
+            x = x << 3999999999;
+            return 0;
+        };
+    ";
+    let limits = Limits {
+        fuel: Some(100),
+        max_memory: Some(10),
+        ..Limits::default()
+    };
+    let start = Instant::now();
+    assert_eq!(
+        run(source, limits).unwrap_err().kind,
+        ErrorKind::OutOfMemory(10)
+    );
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // Integers being computed count too
+    let source = "
+        const main = fn () -> u64 {
+            if (1u4000000000 << 3999999999) == 0u4000000000 {
+                return 1;
+            }
+            return 0;
+        };
+    ";
+    let limits = Limits {
+        max_memory: Some(10),
+        ..Limits::default()
+    };
+    assert_eq!(
+        run(source, limits).unwrap_err().kind,
+        ErrorKind::OutOfMemory(10)
+    );
+}
+
+#[test]
+fn deadline() {
+    let limits = Limits {
+        deadline: Some(Instant::now() + Duration::from_millis(50)),
+        ..Limits::default()
+    };
+    assert_eq!(run(FOREVER, limits).unwrap_err().kind, ErrorKind::Deadline);
+}
@@ -0,0 +1,22 @@
+use rasta_interp::{ErrorKind, Memory, Region, Value};
+
+#[test]
+fn read_str() {
+    let mut memory = Memory::default();
+    let chars = "hi\0".bytes().map(|byte| Value::from(byte as i8)).collect();
+    let ptr = memory.alloc(Region::Heap, chars).unwrap();
+    assert_eq!(memory.read_str(ptr).unwrap(), "hi");
+}
+
+#[test]
+fn read_str_of_floats() {
+    let mut memory = Memory::default();
+    let ptr = memory.alloc(Region::Heap, vec![Value::F64(1.5)]).unwrap();
+    assert_eq!(
+        memory.read_str(ptr),
+        Err(ErrorKind::TypeMismatch {
+            expected: "a character".to_string(),
+            found: Value::F64(1.5),
+        })
+    );
+}
@@ -0,0 +1,96 @@
+use rasta::CompUnit;
+use rasta_interp::{Error, ErrorKind, Interpreter, Value};
+
+fn parse(source: &str) -> CompUnit {
+    cara::parse(source.to_string(), "test.cara".to_string()).unwrap()
+}
+
+/// Call `main` of `source`, which must check
+fn run(source: &str) -> Result<Value, Error> {
+    let unit = parse(source);
+    let mut interp = Interpreter::new(&unit).unwrap();
+    interp.call("main", Vec::new())
+}
+
+#[test]
+fn constant_used_before_its_definition() {
+    let source = "
+        const A = B + 1;
+        const B = 2;
+        const main = fn () -> u64 { return A; };
+    ";
+    assert_eq!(run(source).unwrap(), Value::from(3u64));
+}
+
+#[test]
+fn constant_depending_on_itself() {
+    let unit = parse(
+        "
+        const A = B + 1;
+        const B = A;
+        const main = fn () -> u64 { return A; };
+    ",
+    );
+    let diagnostics = Interpreter::new(&unit).err().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn loop_with_a_literal_start() {
+    let source = "
+        const sum = fn (arg n: u32) -> u32 {
+            var total: u32 = 0;
+            for i in (0, n, 1) { total = total + i; }
+            return total;
+        };
+        const main = fn () -> u32 { return sum(5u32); };
+    ";
+    assert_eq!(run(source).unwrap(), Value::from(10u32));
+}
+
+#[test]
+fn pointer_to_a_local_of_a_finished_block() {
+    let source = "
+        const main = fn () -> u64 {
+            var y: u64 = 0;
+            var p: u64* = &y;
+            {
+                var x: u64 = 1;
+                p = &x;
+                *p = 2;
+            }
+            return *p;
+        };
+    ";
+    let err = run(source).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Dangling);
+    assert_eq!(err.span.text(&parse(source).source_map), Some("*p"));
+}
+
+#[test]
+fn pointer_to_a_loop_variable() {
+    let source = "
+        const main = fn () -> u64 {
+            var y: u64 = 0;
+            var p: u64* = &y;
+            for i in (0, 3, 1) { p = &i; }
+            return *p;
+        };
+    ";
+    assert_eq!(run(source).unwrap_err().kind, ErrorKind::Dangling);
+}
+
+#[test]
+fn locals_of_each_iteration_are_fresh() {
+    let source = "
+        const main = fn () -> u64 {
+            var total: u64 = 0;
+            for i in (0, 4, 1) {
+                var x: u64 = i;
+                total = total + x;
+            }
+            return total;
+        };
+    ";
+    assert_eq!(run(source).unwrap(), Value::from(6u64));
+}
@@ -1,11 +1,10 @@
 use std::{env::args, fs::File};
 use std::io::{Read, Write};
 
-use parser::parse;
+use cara::parse;
+use rasta::DiagnosticFormat;
 use rasta_verilog::{generate_verilog, TOP_MODULE};
 
-mod parser;
-
 fn main() {
 
     let mut args = args();
@@ -15,16 +14,28 @@ fn main() {
         std::process::exit(1);
     }
 
-    let _exe = args.nth(0).unwrap();
-    let source_file_path = args.nth(0).unwrap();
+    let _exe = args.next().unwrap();
+    let source_file_path = args.next().unwrap();
 
     let mut source_file = File::open(source_file_path.clone()).unwrap();
     let mut source = String::new();
     source_file.read_to_string(&mut source).unwrap();
 
-    let ast = parse(source, source_file_path);
-
-    let code = generate_verilog(&ast).unwrap();
+    let ast = match parse(source, source_file_path) {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprint!("{}", err.diagnostic.render(&err.source_map, DiagnosticFormat::Colored));
+            std::process::exit(1);
+        }
+    };
+
+    let code = match generate_verilog(&ast) {
+        Ok(code) => code,
+        Err(err) => {
+            eprint!("{}", err.diagnostic().render(&ast.source_map, DiagnosticFormat::Colored));
+            std::process::exit(1);
+        }
+    };
 
     let top = TOP_MODULE.lock().unwrap().clone().unwrap();
 
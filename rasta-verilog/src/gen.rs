@@ -1,33 +1,70 @@
 use std::sync::Mutex;
 
+use rasta::num_bigint::Sign;
+
 use super::*;
 
 pub static TOP_MODULE: Mutex<Option<String>> = Mutex::new(None);
 
+/// What the generator knows of a program besides its syntax, the types of its expressions
+pub struct Context<'a> {
+    ids: NodeIds<'a>,
+    types: TypeCheck,
+}
+
+impl<'a> Context<'a> {
+    /// Check `ast`, failing with the first problem found
+    pub fn new(ast: &'a CompUnit) -> Result<Self, Error> {
+        let ids = NodeIds::new(ast);
+        let resolution = resolve(ast,&ids);
+        let types = type_check(ast,&ids,&resolution);
+        let diagnostic = resolution.diagnostics.into_iter().chain(types.diagnostics.iter().cloned()).next();
+        if let Some(diagnostic) = diagnostic {
+            let message = diagnostic.message.clone();
+            return Err(Error(ErrorKind::Check(Box::new(diagnostic)),message));
+        }
+        Ok(Self { ids, types })
+    }
+
+    fn exp_type(&self, exp: &Exp) -> Option<&VType> {
+        self.types.types.get(self.ids.exp(exp)?)
+    }
+}
+
 pub trait GenerateVerilog {
     type Out;
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error>;
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error>;
 }
 
 impl GenerateVerilog for VType {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
-        assert_eq!(self.star,0);
-        write!(code.borrow_mut(),"[{}:0]",match self.ty {
-            VTypeEnum::I8 => 8,
-            VTypeEnum::U64 => 64,
-            VTypeEnum::Void => 0,
-            _ => unimplemented!(),
-        }-1).unwrap();
+    fn generate(&self, code: Rc<RefCell<String>>, _cx: &Context) -> Result<Self::Out, Error> {
+        let bits = match &self.ty {
+            _ if self.star > 0 => None,
+            // Floats are passed around as their IEEE 754 bits
+            VTypeEnum::F32 => Some(32),
+            VTypeEnum::F64 => Some(64),
+            ty => ty.bit_width(),
+        };
+        let Some(bits) = bits else {
+            let mut printer = Printer::new();
+            printer.print_vtype(self);
+            let what = format!("Values of type `{}`",printer.finish());
+            return Err(unsupported(&what,self.span.clone()));
+        };
+        if self.ty.is_signed() {
+            write!(code.borrow_mut(),"signed ").unwrap();
+        }
+        write!(code.borrow_mut(),"[{}:0]",bits as i64-1).unwrap();
         Ok(())
     }
 }
 
 impl GenerateVerilog for CompUnit {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         for item in self.global_items.iter() {
-            item.generate(code.clone())?;
+            item.generate(code.clone(),cx)?;
         }
         Ok(())
     }
@@ -35,25 +72,29 @@ impl GenerateVerilog for CompUnit {
 
 impl GenerateVerilog for GlobalItem {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         match self {
-            GlobalItem::ConstDecl(decl) => decl.generate(code),
-            _ => unimplemented!()
+            GlobalItem::ConstDecl(decl) => decl.generate(code,cx),
+            GlobalItem::BuiltinFnCall(call) => Err(unsupported("Builtin function calls",call.span.clone())),
+            GlobalItem::InlineAsm(asm) => Err(unsupported("Inline assembly blocks",asm.span.clone())),
         }
     }
 }
 
 impl GenerateVerilog for ConstDecl {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         write!(code.borrow_mut(),"module {}",self.id).unwrap();
-        self.init.generate(code.clone())?;
+        self.init.generate(code.clone(),cx)?;
         writeln!(code.borrow_mut(),"endmodule").unwrap();
 
         if let Some(attr) = &self.attr {
             if attr.attrs[0] == "top" {
                 if TOP_MODULE.lock().unwrap().is_some() {
-                    panic!("Duplicated top modules! {}",attr.span);
+                    return Err(Error(
+                        ErrorKind::DuplicatedTopModule(attr.span.clone()),
+                        "Duplicated top modules!".into(),
+                    ));
                 }
                 *TOP_MODULE.lock().unwrap() = Some(self.id.clone());
             }
@@ -65,30 +106,32 @@ impl GenerateVerilog for ConstDecl {
 
 impl GenerateVerilog for ConstInitVal {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         match self {
-            Self::Function(func) => func.generate(code),
-            _ => unimplemented!(),
+            Self::Function(func) => func.generate(code,cx),
+            Self::Proto(proto) => Err(unsupported("Function prototypes",proto.span.clone())),
+            Self::Class(class) => Err(unsupported("Classes",class.span.clone())),
+            Self::Exp(exp) => Err(unsupported("Constant values",exp.exp.full_span())),
         }
     }
 }
 
 impl GenerateVerilog for FuncDef {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         write!(code.borrow_mut(),"(").unwrap();
 
         for param in self.params.iter() {
             write!(code.borrow_mut(),"input ").unwrap();
-            param.ty.generate(code.clone())?;
+            param.ty.generate(code.clone(),cx)?;
             write!(code.borrow_mut()," {},",param.id).unwrap();
         }
 
         write!(code.borrow_mut(),"output ").unwrap();
-        self.func_type.generate(code.clone())?;
+        self.func_type.generate(code.clone(),cx)?;
         writeln!(code.borrow_mut(),"  out);").unwrap();
 
-        self.block.generate(code.clone())?;
+        self.block.generate(code.clone(),cx)?;
 
         Ok(())
     }
@@ -96,9 +139,9 @@ impl GenerateVerilog for FuncDef {
 
 impl GenerateVerilog for Block {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         for item in self.items.iter() {
-            item.generate(code.clone())?;
+            item.generate(code.clone(),cx)?;
         }
         Ok(())
     }
@@ -106,20 +149,25 @@ impl GenerateVerilog for Block {
 
 impl GenerateVerilog for BlockItem {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         match self {
-            BlockItem::Stmt(stmt) => stmt.generate(code),
-            _ => unimplemented!(),
+            BlockItem::Stmt(stmt) => stmt.generate(code,cx),
+            BlockItem::Decl(Decl::Const(decl)) => Err(unsupported("Local constants",decl.span.clone())),
+            BlockItem::Decl(Decl::Var(decl)) => Err(unsupported("Variables",decl.span.clone())),
         }
     }
 }
 
 impl GenerateVerilog for Stmt {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         match self {
-            Self::Return(ret) => ret.generate(code),
-            _ => unimplemented!(),
+            Self::Return(ret) => ret.generate(code,cx),
+            // Only empty statements have no span
+            stmt => match stmt.span() {
+                Some(span) => Err(unsupported("Statements other than `return`",span)),
+                None => Ok(()),
+            },
         }
     }
 }
@@ -127,31 +175,49 @@ impl GenerateVerilog for Stmt {
 impl GenerateVerilog for Return {
     type Out = ();
 
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
-        write!(code.borrow_mut(),"assign out = ").unwrap();
-        self.exp.generate(code.clone())?;
-        writeln!(code.borrow_mut(),";").unwrap();
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
+        if let Some(exp) = &self.exp {
+            write!(code.borrow_mut(),"assign out = ").unwrap();
+            exp.generate(code.clone(),cx)?;
+            writeln!(code.borrow_mut(),";").unwrap();
+        }
         Ok(())
     }
 }
 
 impl GenerateVerilog for Exp {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, cx: &Context) -> Result<Self::Out, Error> {
         match self {
             Self::Unary(unary, exp, _span) => {
+                if matches!(unary,UnaryOp::Negative) && cx.exp_type(exp).is_some_and(|ty| ty.is_float()) {
+                    return Err(float_arithmetic(self));
+                }
                 write!(code.borrow_mut(),"{}",match unary {
-                    UnaryOp::Not => "~",
+                    UnaryOp::Not => "!",
+                    UnaryOp::BitNot => "~",
                     UnaryOp::Negative => "-",
                     _ => "",
                 }).unwrap();
-                exp.generate(code)
+                generate_operand(exp,u8::MAX,code,cx)
             },
-            Self::Exp(exp, _span) => exp.generate(code),
-            Self::Number(number) => number.generate(code),
-            Self::LVal(lval) => lval.generate(code),
+            Self::Exp(exp, _span) => exp.generate(code,cx),
+            Self::Number(number) => number.generate(code,cx),
+            // The bits of the float, which is what its vector holds
+            Self::Float(float) => {
+                match cx.exp_type(self).map(|ty| &ty.ty) {
+                    Some(VTypeEnum::F32) => write!(code.borrow_mut(),"32'h{:08X}",(float.num as f32).to_bits()),
+                    _ => write!(code.borrow_mut(),"64'h{:016X}",float.num.to_bits()),
+                }.unwrap();
+                Ok(())
+            },
+            Self::LVal(lval) => lval.generate(code,cx),
             Self::Binary(lhs, op, rhs, _span) => {
-                lhs.generate(code.clone())?;
+                if cx.exp_type(lhs).is_some_and(|ty| ty.is_float()) {
+                    return Err(float_arithmetic(self));
+                }
+                let prec = precedence(op);
+                generate_operand(lhs,prec,code.clone(),cx)?;
                 write!(code.borrow_mut()," {} ",match op {
                     BinaryOp::Eq => "==",
                     BinaryOp::Neq => "!=",
@@ -164,26 +230,110 @@ impl GenerateVerilog for Exp {
                     BinaryOp::Gt => ">",
                     BinaryOp::Lt => "<",
                     BinaryOp::Mod => "%",
+                    BinaryOp::BitAnd => "&",
+                    BinaryOp::BitOr => "|",
+                    BinaryOp::BitXor => "^",
+                    BinaryOp::Shl => "<<",
+                    // An arithmetic shift for signed operands, a logical one otherwise
+                    BinaryOp::Shr if cx.exp_type(lhs).is_some_and(|ty| ty.ty.is_signed()) => ">>>",
+                    BinaryOp::Shr => ">>",
+                    BinaryOp::And => "&&",
+                    BinaryOp::Or => "||",
                 }).unwrap();
-                rhs.generate(code.clone())?;
+                // Operators are left associative, so an equal right operand needs parens
+                generate_operand(rhs,prec+1,code.clone(),cx)?;
                 Ok(())
             }
-            _ => unimplemented!(),
+            Self::FuncCall(call) => Err(unsupported("Function calls",call.span.clone())),
+            Self::GetAddr(get_addr) => Err(unsupported("Pointers",get_addr.span.clone())),
+            Self::Deref(deref) => Err(unsupported("Pointers",deref.get_span())),
+            Self::Str(_, span) => Err(unsupported("Strings",span.clone())),
+            Self::Array(array) => Err(unsupported("Arrays",array.get_span())),
         }
     }
 }
 
+/// Something the checker accepts that has no Verilog counterpart yet
+fn unsupported(what: &str, span: Span) -> Error {
+    Error(ErrorKind::Unsupported(span),format!("{} are not supported!",what))
+}
+
+/// Verilog has no operators for the IEEE 754 bits floats are held as
+fn float_arithmetic(exp: &Exp) -> Error {
+    Error(
+        ErrorKind::FloatArithmetic(exp.full_span()),
+        "Floating-point arithmetic is not supported!".into(),
+    )
+}
+
+/// How tightly a Verilog operator binds. Unlike in Cara, `&`, `^` and `|` bind looser
+/// than comparisons.
+fn precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Or => 1,
+        BinaryOp::And => 2,
+        BinaryOp::BitOr => 3,
+        BinaryOp::BitXor => 4,
+        BinaryOp::BitAnd => 5,
+        BinaryOp::Eq | BinaryOp::Neq => 6,
+        BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge => 7,
+        BinaryOp::Shl | BinaryOp::Shr => 8,
+        BinaryOp::Add | BinaryOp::Sub => 9,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 10,
+    }
+}
+
+/// Generate `exp` as an operand, in parentheses if it binds looser than `prec`
+fn generate_operand(exp: &Exp, prec: u8, code: Rc<RefCell<String>>, cx: &Context) -> Result<(), Error> {
+    let paren = match exp {
+        Exp::Binary(_, op, _, _) => precedence(op) < prec,
+        _ => false,
+    };
+    if paren {
+        write!(code.borrow_mut(),"(").unwrap();
+    }
+    exp.generate(code.clone(),cx)?;
+    if paren {
+        write!(code.borrow_mut(),")").unwrap();
+    }
+    Ok(())
+}
+
 impl GenerateVerilog for Number {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
-        write!(code.borrow_mut(),"{}",self.num).unwrap();
+    fn generate(&self, code: Rc<RefCell<String>>, _cx: &Context) -> Result<Self::Out, Error> {
+        let mut code = code.borrow_mut();
+        if self.num.sign() == Sign::Minus {
+            write!(code,"-").unwrap();
+        }
+        let magnitude = self.num.magnitude();
+
+        // Suffixed literals are sized, `8u8` is `8'd8`
+        let bits = self.suffix.as_ref().and_then(|ty| ty.bit_width());
+        if self.radix == Radix::Decimal && bits.is_none() {
+            write!(code,"{}",magnitude).unwrap();
+            return Ok(());
+        }
+        if let Some(bits) = bits {
+            write!(code,"{}",bits).unwrap();
+        }
+        write!(code,"'").unwrap();
+        if self.suffix.as_ref().is_some_and(|ty| ty.is_signed()) {
+            write!(code,"s").unwrap();
+        }
+        match self.radix {
+            Radix::Binary => write!(code,"b{:b}",magnitude),
+            Radix::Octal => write!(code,"o{:o}",magnitude),
+            Radix::Decimal => write!(code,"d{}",magnitude),
+            Radix::Hex => write!(code,"h{:X}",magnitude),
+        }.unwrap();
         Ok(())
     }
 }
 
 impl GenerateVerilog for LVal {
     type Out = ();
-    fn generate(&self, code: Rc<RefCell<String>>) -> Result<Self::Out, Error> {
+    fn generate(&self, code: Rc<RefCell<String>>, _cx: &Context) -> Result<Self::Out, Error> {
         write!(code.borrow_mut(),"{}",self.ids.join(".")).unwrap();
         Ok(())
     }
@@ -9,17 +9,39 @@ pub use gen::*;
 
 #[derive(Debug)]
 pub enum ErrorKind {
-
+    DuplicatedTopModule(Span),
+    /// The program doesn't check, its types are needed to generate it
+    Check(Box<Diagnostic>),
+    FloatArithmetic(Span),
+    Unsupported(Span),
 }
 
 #[derive(Debug)]
 pub struct Error(pub ErrorKind, pub String);
 
+impl Error {
+    /// The error as a diagnostic that can be rendered with the source map of the AST
+    pub fn diagnostic(&self) -> Diagnostic {
+        match &self.0 {
+            ErrorKind::DuplicatedTopModule(span) => Diagnostic::error(self.1.clone())
+                .with_primary(span.clone(), "second top module marked here")
+                .with_help("only one module can have the `top` attribute"),
+            ErrorKind::Check(diagnostic) => (**diagnostic).clone(),
+            ErrorKind::FloatArithmetic(span) => Diagnostic::error(self.1.clone())
+                .with_primary(span.clone(), "operates on floats")
+                .with_help("floats are generated as vectors of their IEEE 754 bits"),
+            ErrorKind::Unsupported(span) => Diagnostic::error(self.1.clone())
+                .with_primary(span.clone(), "cannot be generated as Verilog"),
+        }
+    }
+}
+
 
 
 pub fn generate_verilog(ast: &CompUnit) -> Result<String, Error> {
+    let cx = Context::new(ast)?;
     let code = Rc::new(RefCell::new(String::new()));
-    ast.generate(code.clone())?;
+    ast.generate(code.clone(),&cx)?;
     let code = code.borrow().clone();
     
     Ok(code)
@@ -0,0 +1,174 @@
+use rasta_verilog::*;
+
+// No module is marked `top`, the top module is global and tests run in parallel
+
+fn generate(source: &str) -> Result<String, Error> {
+    let unit = cara::parse(source.to_string(), "test.cara".to_string()).unwrap();
+    generate_verilog(&unit)
+}
+
+/// The `assign` generated for `return exp;` in a function of `params` returning `ty`
+fn assign(params: &str, ty: &str, exp: &str) -> String {
+    let source = format!("const m = fn ({}) -> {} {{ return {}; }};", params, ty, exp);
+    let code = generate(&source).unwrap();
+    let line = code
+        .lines()
+        .find(|line| line.starts_with("assign"))
+        .unwrap();
+    line.to_string()
+}
+
+/// The message of the error generating `source` and the source its diagnostic points at
+fn error(source: &str) -> (String, String) {
+    let unit = cara::parse(source.to_string(), "test.cara".to_string()).unwrap();
+    let err = generate_verilog(&unit).unwrap_err();
+    let span = err.diagnostic().labels[0].span.clone();
+    // The parser's spans of types take the whitespace after them
+    let text = span.text(&unit.source_map).unwrap().trim_end();
+    (err.1, text.to_string())
+}
+
+#[test]
+fn integer_types() {
+    let code = generate(
+        "const m = fn (arg a: u8, arg b: i16, arg c: u3, arg d: i100, arg e: bool) -> u128 {
+            return 8u128;
+        };",
+    )
+    .unwrap();
+    assert_eq!(
+        code,
+        "module m(input [7:0] a,input signed [15:0] b,input [2:0] c,input signed [99:0] d,\
+         input [0:0] e,output [127:0]  out);\nassign out = 128'd8;\nendmodule\n"
+    );
+}
+
+#[test]
+fn sized_literals() {
+    assert_eq!(assign("", "u16", "0xFFu16"), "assign out = 16'hFF;");
+    assert_eq!(assign("", "i8", "-5i8"), "assign out = -8'sd5;");
+    assert_eq!(assign("", "u8", "0b101u8"), "assign out = 8'b101;");
+    assert_eq!(assign("", "u64", "0o17"), "assign out = 'o17;");
+}
+
+#[test]
+fn types_without_a_width() {
+    assert_eq!(
+        error("const m = fn (arg a: u64*) -> u64 { return 1; };"),
+        (
+            "Values of type `u64*` are not supported!".to_string(),
+            "u64*".to_string()
+        )
+    );
+    assert_eq!(
+        error("const m = fn (arg a: u64) -> void { return; };"),
+        (
+            "Values of type `void` are not supported!".to_string(),
+            "void".to_string()
+        )
+    );
+}
+
+#[test]
+fn unsupported_items() {
+    let cases = [
+        ("asm(\"nop\");", "Inline assembly blocks are not supported!"),
+        ("[do_magic]()", "Builtin function calls are not supported!"),
+        ("const a = 1;", "Constant values are not supported!"),
+        (
+            "const p = fn () -> u8;",
+            "Function prototypes are not supported!",
+        ),
+        ("const P = class { x: u8 };", "Classes are not supported!"),
+    ];
+    for (source, message) in cases {
+        assert_eq!(error(source).0, message, "{}", source);
+    }
+}
+
+#[test]
+fn unsupported_statements() {
+    let cases = [
+        (
+            "var x: u8 = 1;",
+            "Variables are not supported!",
+            "var x: u8 = 1;",
+        ),
+        (
+            "const c = 1;",
+            "Local constants are not supported!",
+            "const c = 1;",
+        ),
+        (
+            "while a { }",
+            "Statements other than `return` are not supported!",
+            "while a { }",
+        ),
+        ("return m(a);", "Function calls are not supported!", "m(a)"),
+        ("return *(&a);", "Pointers are not supported!", "*(&a)"),
+    ];
+    for (body, message, text) in cases {
+        let source = format!("const m = fn (arg a: u8) -> u8 {{ {} return a; }};", body);
+        let (found, span) = error(&source);
+        assert_eq!((found.as_str(), span.as_str()), (message, text), "{}", body);
+    }
+}
+
+#[test]
+fn empty_statements() {
+    let code = generate("const m = fn (arg a: u8) -> u8 { ; return a; };").unwrap();
+    assert!(code.contains("assign out = a;"));
+}
+
+#[test]
+fn float_literals_are_their_bits() {
+    assert_eq!(assign("", "f32", "1.5"), "assign out = 32'h3FC00000;");
+    assert_eq!(
+        assign("", "f64", "1200.0"),
+        "assign out = 64'h4092C00000000000;"
+    );
+    assert_eq!(
+        assign("", "f64", "0.1"),
+        "assign out = 64'h3FB999999999999A;"
+    );
+    assert_eq!(assign("arg a: f32", "f32", "a"), "assign out = a;");
+}
+
+#[test]
+fn float_arithmetic() {
+    let cases = [
+        ("f64", "a + 1.5"),
+        ("f64", "2.0 * a"),
+        ("f64", "-a"),
+        ("bool", "a < 1.0"),
+        ("bool", "a == a"),
+    ];
+    for (ty, exp) in cases {
+        let source = format!("const m = fn (arg a: f64) -> {} {{ return {}; }};", ty, exp);
+        assert_eq!(
+            error(&source),
+            (
+                "Floating-point arithmetic is not supported!".to_string(),
+                exp.to_string()
+            ),
+            "{}",
+            exp
+        );
+    }
+}
+
+#[test]
+fn shift_right_follows_signedness() {
+    assert_eq!(assign("arg a: i8", "i8", "a >> 1"), "assign out = a >>> 1;");
+    assert_eq!(
+        assign("arg a: i64", "i64", "a >> 3"),
+        "assign out = a >>> 3;"
+    );
+    assert_eq!(assign("arg a: u8", "u8", "a >> 1"), "assign out = a >> 1;");
+    assert_eq!(assign("arg a: u3", "u3", "a >> 1"), "assign out = a >> 1;");
+    assert_eq!(assign("arg a: i8", "i8", "a << 1"), "assign out = a << 1;");
+    assert_eq!(
+        assign("arg a: i8, arg b: u8", "u8", "b >> 1 >> 2"),
+        "assign out = b >> 1 >> 2;"
+    );
+}
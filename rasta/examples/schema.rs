@@ -0,0 +1,4 @@
+fn main() {
+    let schema = rasta::json_schema();
+    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+}
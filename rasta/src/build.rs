@@ -0,0 +1,612 @@
+//! Concise constructors for AST nodes, for tools that generate code and for tests.
+//!
+//! Every node gets a dummy span, and sub-expressions are boxed as needed:
+//!
+//! ```
+//! use rasta::build::*;
+//!
+//! let inc = func("inc", u64())
+//!     .param("a", u64())
+//!     .ret(add(var("a"), num(1)));
+//! let unit = unit([inc.into()]);
+//! assert_eq!(
+//!     rasta::unparse(&unit),
+//!     "const inc = fn (arg a: u64) -> u64 {\n    return a + 1;\n};\n"
+//! );
+//! ```
+
+use super::*;
+
+fn span() -> Span {
+    Span::dummy()
+}
+
+/// A compilation unit with no source files
+pub fn unit(items: impl IntoIterator<Item = GlobalItem>) -> CompUnit {
+    CompUnit {
+        global_items: items.into_iter().collect(),
+        span: span(),
+        source_map: SourceMap::new(),
+    }
+}
+
+fn vtype(ty: VTypeEnum) -> VType {
+    VType {
+        ty,
+        star: 0,
+        span: span(),
+    }
+}
+
+pub fn u8() -> VType {
+    vtype(VTypeEnum::U8)
+}
+
+pub fn u16() -> VType {
+    vtype(VTypeEnum::U16)
+}
+
+pub fn u32() -> VType {
+    vtype(VTypeEnum::U32)
+}
+
+pub fn u64() -> VType {
+    vtype(VTypeEnum::U64)
+}
+
+pub fn u128() -> VType {
+    vtype(VTypeEnum::U128)
+}
+
+pub fn i8() -> VType {
+    vtype(VTypeEnum::I8)
+}
+
+pub fn i16() -> VType {
+    vtype(VTypeEnum::I16)
+}
+
+pub fn i32() -> VType {
+    vtype(VTypeEnum::I32)
+}
+
+pub fn i64() -> VType {
+    vtype(VTypeEnum::I64)
+}
+
+pub fn i128() -> VType {
+    vtype(VTypeEnum::I128)
+}
+
+/// An unsigned integer of `bits` bits
+pub fn uint(bits: u32) -> VType {
+    vtype(VTypeEnum::int(false, bits))
+}
+
+/// A signed integer of `bits` bits
+pub fn sint(bits: u32) -> VType {
+    vtype(VTypeEnum::int(true, bits))
+}
+
+pub fn f32() -> VType {
+    vtype(VTypeEnum::F32)
+}
+
+pub fn f64() -> VType {
+    vtype(VTypeEnum::F64)
+}
+
+pub fn bool() -> VType {
+    vtype(VTypeEnum::Bool)
+}
+
+pub fn void() -> VType {
+    vtype(VTypeEnum::Void)
+}
+
+/// A type referred to by name, like a class
+pub fn named(name: &str) -> VType {
+    vtype(VTypeEnum::Others(name.to_string()))
+}
+
+/// A pointer to `ty`
+pub fn ptr(mut ty: VType) -> VType {
+    ty.star += 1;
+    ty
+}
+
+pub fn num(num: impl Into<BigInt>) -> Exp {
+    number(num.into(), None, Radix::Decimal)
+}
+
+/// A literal of type `ty`, like `8u8`
+pub fn suffixed(num: impl Into<BigInt>, ty: VType) -> Exp {
+    number(num.into(), Some(ty.ty), Radix::Decimal)
+}
+
+pub fn hex(num: impl Into<BigInt>) -> Exp {
+    number(num.into(), None, Radix::Hex)
+}
+
+pub fn bin(num: impl Into<BigInt>) -> Exp {
+    number(num.into(), None, Radix::Binary)
+}
+
+fn number(num: BigInt, suffix: Option<VTypeEnum>, radix: Radix) -> Exp {
+    Exp::Number(Number {
+        num,
+        suffix,
+        radix,
+        span: span(),
+    })
+}
+
+pub fn float(num: f64) -> Exp {
+    Exp::Float(Float { num, span: span() })
+}
+
+pub fn string(string: &str) -> Exp {
+    Exp::Str(string.to_string(), span())
+}
+
+pub fn lval(id: &str) -> LVal {
+    LVal {
+        ids: vec![id.to_string()],
+        span: span(),
+        exp: None,
+    }
+}
+
+/// Read the variable `id`
+pub fn var(id: &str) -> Exp {
+    Exp::LVal(Box::new(lval(id)))
+}
+
+/// An expression in parentheses
+pub fn paren(exp: Exp) -> Exp {
+    Exp::Exp(Box::new(exp), span())
+}
+
+pub fn unary(op: UnaryOp, exp: Exp) -> Exp {
+    Exp::Unary(op, Box::new(exp), span())
+}
+
+pub fn neg(exp: Exp) -> Exp {
+    unary(UnaryOp::Negative, exp)
+}
+
+pub fn not(exp: Exp) -> Exp {
+    unary(UnaryOp::Not, exp)
+}
+
+pub fn bit_not(exp: Exp) -> Exp {
+    unary(UnaryOp::BitNot, exp)
+}
+
+pub fn binary(lhs: Exp, op: BinaryOp, rhs: Exp) -> Exp {
+    Exp::Binary(Box::new(lhs), op, Box::new(rhs), span())
+}
+
+pub fn add(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Add, rhs)
+}
+
+pub fn sub(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Sub, rhs)
+}
+
+pub fn mul(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Mul, rhs)
+}
+
+pub fn div(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Div, rhs)
+}
+
+/// The remainder, `lhs % rhs`
+pub fn rem(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Mod, rhs)
+}
+
+pub fn lt(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Lt, rhs)
+}
+
+pub fn gt(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Gt, rhs)
+}
+
+pub fn le(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Le, rhs)
+}
+
+pub fn ge(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Ge, rhs)
+}
+
+pub fn eq(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Eq, rhs)
+}
+
+pub fn ne(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Neq, rhs)
+}
+
+pub fn bit_and(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::BitAnd, rhs)
+}
+
+pub fn bit_or(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::BitOr, rhs)
+}
+
+pub fn bit_xor(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::BitXor, rhs)
+}
+
+pub fn shl(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Shl, rhs)
+}
+
+pub fn shr(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Shr, rhs)
+}
+
+pub fn and(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::And, rhs)
+}
+
+pub fn or(lhs: Exp, rhs: Exp) -> Exp {
+    binary(lhs, BinaryOp::Or, rhs)
+}
+
+pub fn call(func: &str, args: impl IntoIterator<Item = Exp>) -> Exp {
+    Exp::FuncCall(FuncCall {
+        ids: vec![func.to_string()],
+        args: args.into_iter().collect(),
+        span: span(),
+    })
+}
+
+/// `&id`
+pub fn addr_of(id: &str) -> Exp {
+    Exp::GetAddr(Box::new(GetAddr {
+        lval: lval(id),
+        span: span(),
+    }))
+}
+
+/// `*id`
+pub fn deref(id: &str) -> Deref {
+    Deref::DerefId(lval(id), span())
+}
+
+/// `*(exp)`
+pub fn deref_exp(exp: Exp) -> Deref {
+    Deref::DerefExp(exp, span())
+}
+
+/// `id[index]`
+pub fn index(id: &str, index: Exp) -> Deref {
+    Deref::DerefPtr(lval(id), index, span())
+}
+
+/// `(exp)[index]`
+pub fn index_exp(exp: Exp, index: Exp) -> Deref {
+    Deref::DerefPtrExp(exp, index, span())
+}
+
+/// `[values...]`
+pub fn array(values: impl IntoIterator<Item = Exp>) -> Exp {
+    Exp::Array(Box::new(Array::List(values.into_iter().collect(), span())))
+}
+
+/// `[value; count]`
+pub fn array_repeat(value: Exp, count: Exp) -> Exp {
+    Exp::Array(Box::new(Array::Template(
+        value,
+        ConstExp { exp: count },
+        span(),
+    )))
+}
+
+pub fn block(items: impl IntoIterator<Item = BlockItem>) -> Block {
+    Block {
+        items: items.into_iter().collect(),
+        span: span(),
+    }
+}
+
+/// `var id: ty = init;`
+pub fn var_decl(id: &str, ty: VType, init: Exp) -> VarDecl {
+    VarDecl {
+        id: id.to_string(),
+        ty,
+        init: InitVal { exp: init },
+        span: span(),
+    }
+}
+
+/// `const id = exp;`
+pub fn constant(id: &str, exp: Exp) -> ConstDecl {
+    ConstDecl {
+        attr: None,
+        id: id.to_string(),
+        init: ConstInitVal::Exp(ConstExp { exp }),
+        span: span(),
+    }
+}
+
+/// `id = exp;`
+pub fn assign(id: &str, exp: Exp) -> Stmt {
+    Stmt::Assign(Assign::WriteVar(lval(id), exp, span()))
+}
+
+/// `*p = exp;`, `p[i] = exp;` and so on
+pub fn store(deref: Deref, exp: Exp) -> Stmt {
+    Stmt::Assign(Assign::WritePtr(deref, exp, span()))
+}
+
+/// An expression statement
+pub fn exp_stmt(exp: Exp) -> Stmt {
+    Stmt::Exp(Some(exp))
+}
+
+pub fn ret(exp: Exp) -> Stmt {
+    Stmt::Return(Return {
+        exp: Some(exp),
+        span: span(),
+    })
+}
+
+pub fn ret_void() -> Stmt {
+    Stmt::Return(Return {
+        exp: None,
+        span: span(),
+    })
+}
+
+pub fn if_(cond: Exp, then: Block) -> Stmt {
+    Stmt::If(If {
+        cond,
+        then,
+        else_then: None,
+        span: span(),
+    })
+}
+
+pub fn if_else(cond: Exp, then: Block, else_then: Block) -> Stmt {
+    Stmt::If(If {
+        cond,
+        then,
+        else_then: Some(else_then),
+        span: span(),
+    })
+}
+
+pub fn while_(cond: Exp, then: Block) -> Stmt {
+    Stmt::While(While {
+        cond,
+        then,
+        span: span(),
+    })
+}
+
+/// `for var in (start, end, step) then`
+pub fn for_(var: &str, start: Exp, end: Exp, step: Exp, then: Block) -> Stmt {
+    Stmt::For(For {
+        var: var.to_string(),
+        start,
+        end,
+        step,
+        then,
+        span: span(),
+    })
+}
+
+pub fn break_() -> Stmt {
+    Stmt::Terminator(Terminator::Break(span()))
+}
+
+pub fn continue_() -> Stmt {
+    Stmt::Terminator(Terminator::Continue(span()))
+}
+
+/// `asm(asm, in id = exp...)` with no outputs, add them with [`asm_out`]
+pub fn asm<'a>(asm: &str, inputs: impl IntoIterator<Item = (&'a str, Exp)>) -> InlineAsm {
+    InlineAsm {
+        asm: asm.to_string(),
+        constraints: inputs
+            .into_iter()
+            .map(|(id, exp)| AsmConstraint::In(id.to_string(), exp, span()))
+            .collect(),
+        span: span(),
+    }
+}
+
+/// Add `out id = target` to an inline asm
+pub fn asm_out(mut asm: InlineAsm, id: &str, target: &str) -> InlineAsm {
+    asm.constraints
+        .push(AsmConstraint::Out(id.to_string(), lval(target), span()));
+    asm
+}
+
+/// Start a function named `name` returning `func_type`
+pub fn func(name: &str, func_type: VType) -> FuncBuilder {
+    FuncBuilder {
+        name: name.to_string(),
+        attrs: Vec::new(),
+        params: Vec::new(),
+        func_type,
+        items: Vec::new(),
+    }
+}
+
+/// Start a proto function named `name` returning `func_type`
+pub fn proto(name: &str, func_type: VType) -> ProtoBuilder {
+    ProtoBuilder {
+        name: name.to_string(),
+        params: Vec::new(),
+        func_type,
+    }
+}
+
+fn param(id: &str, ty: VType) -> Param {
+    Param {
+        ty,
+        id: id.to_string(),
+        span: span(),
+    }
+}
+
+/// A function definition under construction, see [`func`]
+#[derive(Debug)]
+pub struct FuncBuilder {
+    name: String,
+    attrs: Vec<String>,
+    params: Vec<Param>,
+    func_type: VType,
+    items: Vec<BlockItem>,
+}
+
+impl FuncBuilder {
+    /// Add an attribute like `top`
+    pub fn attr(mut self, attr: &str) -> Self {
+        self.attrs.push(attr.to_string());
+        self
+    }
+
+    pub fn param(mut self, id: &str, ty: VType) -> Self {
+        self.params.push(param(id, ty));
+        self
+    }
+
+    /// Append a statement or declaration to the body
+    pub fn item(mut self, item: impl Into<BlockItem>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    /// Append `var id: ty = init;` to the body
+    pub fn var(self, id: &str, ty: VType, init: Exp) -> Self {
+        self.item(var_decl(id, ty, init))
+    }
+
+    /// Append `return exp;` to the body
+    pub fn ret(self, exp: Exp) -> Self {
+        self.item(ret(exp))
+    }
+
+    /// The function as `const name = fn (...) -> ty { ... };`
+    pub fn build(self) -> ConstDecl {
+        ConstDecl {
+            attr: (!self.attrs.is_empty()).then(|| Attributes {
+                attrs: self.attrs,
+                span: span(),
+            }),
+            id: self.name,
+            init: ConstInitVal::Function(FuncDef {
+                func_type: self.func_type,
+                block: block(self.items),
+                params: self.params,
+                span: span(),
+            }),
+            span: span(),
+        }
+    }
+}
+
+/// A proto function under construction, see [`proto`]
+#[derive(Debug)]
+pub struct ProtoBuilder {
+    name: String,
+    params: Vec<Param>,
+    func_type: VType,
+}
+
+impl ProtoBuilder {
+    pub fn param(mut self, id: &str, ty: VType) -> Self {
+        self.params.push(param(id, ty));
+        self
+    }
+
+    /// The proto as `const name = fn (...) -> ty;`
+    pub fn build(self) -> ConstDecl {
+        ConstDecl {
+            attr: None,
+            id: self.name,
+            init: ConstInitVal::Proto(ProtoDef {
+                func_type: self.func_type,
+                params: self.params,
+                span: span(),
+            }),
+            span: span(),
+        }
+    }
+}
+
+impl From<FuncBuilder> for ConstDecl {
+    fn from(builder: FuncBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<ProtoBuilder> for ConstDecl {
+    fn from(builder: ProtoBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<FuncBuilder> for GlobalItem {
+    fn from(builder: FuncBuilder) -> Self {
+        GlobalItem::ConstDecl(builder.build())
+    }
+}
+
+impl From<ProtoBuilder> for GlobalItem {
+    fn from(builder: ProtoBuilder) -> Self {
+        GlobalItem::ConstDecl(builder.build())
+    }
+}
+
+impl From<ConstDecl> for GlobalItem {
+    fn from(decl: ConstDecl) -> Self {
+        GlobalItem::ConstDecl(decl)
+    }
+}
+
+impl From<InlineAsm> for GlobalItem {
+    fn from(asm: InlineAsm) -> Self {
+        GlobalItem::InlineAsm(asm)
+    }
+}
+
+impl From<Stmt> for BlockItem {
+    fn from(stmt: Stmt) -> Self {
+        BlockItem::Stmt(stmt)
+    }
+}
+
+impl From<VarDecl> for BlockItem {
+    fn from(decl: VarDecl) -> Self {
+        BlockItem::Decl(Decl::Var(decl))
+    }
+}
+
+impl From<ConstDecl> for BlockItem {
+    fn from(decl: ConstDecl) -> Self {
+        BlockItem::Decl(Decl::Const(decl))
+    }
+}
+
+impl From<InlineAsm> for BlockItem {
+    fn from(asm: InlineAsm) -> Self {
+        BlockItem::Stmt(Stmt::InlineAsm(asm))
+    }
+}
+
+impl From<Block> for BlockItem {
+    fn from(block: Block) -> Self {
+        BlockItem::Stmt(Stmt::Block(block))
+    }
+}
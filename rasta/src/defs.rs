@@ -1,14 +1,14 @@
 use super::*;
 
 /// The function attributes
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Attributes {
     pub attrs: Vec<String>,
     pub span: Span,
 }
 
 /// The function definitions
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct FuncDef {
     pub func_type: VType,
     pub block: Block,
@@ -17,62 +17,62 @@ pub struct FuncDef {
 }
 
 /// Proto function definitions
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ProtoDef {
     pub func_type: VType,
     pub params: Vec<Param>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Param {
     pub ty: VType,
     pub id: String,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Decl {
     Const(ConstDecl),
     Var(VarDecl),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ClassMember {
     pub ty: VType,
     pub id: String,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ClassDef {
     pub members: Vec<ClassMember>,
     pub consts: Vec<ConstDecl>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct NewClassMember {
     pub id: String,
     pub val: Exp,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct NewClass {
     pub class: String,
     pub members: Vec<NewClassMember>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct While {
     pub cond: Exp,
     pub then: Block,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ConstDecl {
     pub attr: Option<Attributes>,
     pub id: String,
@@ -80,7 +80,7 @@ pub struct ConstDecl {
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum ConstInitVal {
     Exp(ConstExp),
     Function(FuncDef),
@@ -88,7 +88,7 @@ pub enum ConstInitVal {
     Class(ClassDef),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct VarDecl {
     pub id: String,
     pub ty: VType,
@@ -96,7 +96,7 @@ pub struct VarDecl {
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InitVal {
     pub exp: Exp,
 }
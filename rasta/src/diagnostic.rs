@@ -0,0 +1,380 @@
+use std::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use super::*;
+
+/// How bad a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+            Self::Help => "help",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Self::Error => "1;31",
+            Self::Warning => "1;33",
+            Self::Note => "1;32",
+            Self::Help => "1;36",
+        }
+    }
+}
+
+/// A span pointed at by a [`Diagnostic`], with an optional message
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    /// Primary labels show where the problem is, secondary ones add context
+    pub primary: bool,
+}
+
+/// The output of [`Diagnostic::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    Plain,
+    Colored,
+    Json,
+}
+
+/// An error, warning or other message about the code
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_primary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Render the diagnostic, looking up the source of its labels in `map`
+    pub fn render(&self, map: &SourceMap, format: DiagnosticFormat) -> String {
+        match format {
+            DiagnosticFormat::Plain => TextRenderer { map, colored: false }.render(self),
+            DiagnosticFormat::Colored => TextRenderer { map, colored: true }.render(self),
+            DiagnosticFormat::Json => self.to_json(map).to_string(),
+        }
+    }
+
+    /// The diagnostic as a JSON object, with the locations of its labels resolved
+    pub fn to_json(&self, map: &SourceMap) -> serde_json::Value {
+        let position = |pos: Option<(usize, usize)>| match pos {
+            Some((line, column)) => serde_json::json!({ "line": line, "column": column }),
+            None => serde_json::Value::Null,
+        };
+        let labels: Vec<_> = self
+            .labels
+            .iter()
+            .map(|label| {
+                let range = label.span.byte_range();
+                serde_json::json!({
+                    "primary": label.primary,
+                    "message": label.message,
+                    "file": map.file(label.span.file()).map(|file| file.name()),
+                    "start": position(label.span.start(map)),
+                    "end": position(label.span.end(map)),
+                    "bytes": [range.start, range.end],
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "severity": self.severity,
+            "code": self.code,
+            "message": self.message,
+            "labels": labels,
+            "notes": self.notes,
+            "help": self.help,
+        })
+    }
+}
+
+/// Where a label starts and ends, as 1-based lines and 0-based display columns
+struct Location<'a> {
+    label: &'a Label,
+    file: &'a SourceFile,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl<'a> Location<'a> {
+    fn new(label: &'a Label, map: &'a SourceMap) -> Option<Self> {
+        let file = map.file(label.span.file())?;
+        let start = label.span.start(map)?;
+        let mut end = label.span.end(map)?;
+
+        // A span ending right after a line break ends on the line before it
+        if end.1 == 1 && end.0 > start.0 {
+            let line = file.line(end.0 - 1).unwrap_or("");
+            end = (end.0 - 1, line.chars().count() + 1);
+        }
+
+        let start = (start.0, display_width(file.line(start.0)?, start.1 - 1));
+        let end = (end.0, display_width(file.line(end.0)?, end.1 - 1));
+        Some(Self {
+            label,
+            file,
+            start,
+            end,
+        })
+    }
+
+    /// The lines shown for the label, long spans only show their first and last two lines
+    fn lines(&self) -> Vec<usize> {
+        if self.end.0 - self.start.0 < 4 {
+            (self.start.0..=self.end.0).collect()
+        } else {
+            vec![self.start.0, self.start.0 + 1, self.end.0 - 1, self.end.0]
+        }
+    }
+}
+
+const TAB_WIDTH: usize = 4;
+
+/// The display width of the first `chars` characters of a line
+fn display_width(line: &str, chars: usize) -> usize {
+    line.chars().take(chars).map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    if c == '\t' {
+        TAB_WIDTH
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+struct TextRenderer<'a> {
+    map: &'a SourceMap,
+    colored: bool,
+}
+
+impl TextRenderer<'_> {
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.colored && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let severity = diagnostic.severity;
+
+        let title = match &diagnostic.code {
+            Some(code) => format!("{}[{}]", severity.name(), code),
+            None => severity.name().to_string(),
+        };
+        writeln!(
+            out,
+            "{}{}",
+            self.paint(severity.color(), &title),
+            self.paint("1", &format!(": {}", diagnostic.message))
+        )
+        .unwrap();
+
+        // Primary labels first, then group the labels by file in order of appearance
+        let mut labels: Vec<&Label> = diagnostic.labels.iter().collect();
+        labels.sort_by_key(|label| !label.primary);
+        let mut groups: Vec<(FileId, Vec<&Label>)> = Vec::new();
+        for label in labels {
+            match groups.iter_mut().find(|(file, _)| *file == label.span.file()) {
+                Some((_, labels)) => labels.push(label),
+                None => groups.push((label.span.file(), vec![label])),
+            }
+        }
+
+        let gutter = diagnostic
+            .labels
+            .iter()
+            .filter_map(|label| label.span.end(self.map))
+            .map(|(line, _)| line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let pad = " ".repeat(gutter);
+
+        for (index, (_, labels)) in groups.iter().enumerate() {
+            let arrow = if index == 0 { "-->" } else { ":::" };
+            let locations: Vec<_> = labels
+                .iter()
+                .filter_map(|label| Location::new(label, self.map))
+                .collect();
+
+            let Some(first) = locations.first() else {
+                writeln!(out, "{}{} <generated>", pad, self.paint("1;34", arrow)).unwrap();
+                continue;
+            };
+            let (line, column) = first.label.span.start(self.map).unwrap();
+            writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                pad,
+                self.paint("1;34", arrow),
+                first.file.name(),
+                line,
+                column
+            )
+            .unwrap();
+            writeln!(out, "{} {}", pad, self.paint("1;34", "|")).unwrap();
+
+            self.render_snippet(&mut out, severity, &locations, gutter);
+        }
+
+        for (kind, messages) in [("note", &diagnostic.notes), ("help", &diagnostic.help)] {
+            for message in messages {
+                writeln!(
+                    out,
+                    "{} {} {}: {}",
+                    pad,
+                    self.paint("1;34", "="),
+                    self.paint("1", kind),
+                    message
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+
+    fn render_snippet(
+        &self,
+        out: &mut String,
+        severity: Severity,
+        locations: &[Location],
+        gutter: usize,
+    ) {
+        let file = locations[0].file;
+        let mut lines: Vec<usize> = locations.iter().flat_map(Location::lines).collect();
+        lines.sort_unstable();
+        lines.dedup();
+
+        let pad = " ".repeat(gutter);
+        let bar = self.paint("1;34", "|");
+        let mut previous = None;
+        for line in lines {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                writeln!(out, "{}", self.paint("1;34", "...")).unwrap();
+            }
+            previous = Some(line);
+
+            let text = file.line(line).unwrap_or("");
+            let expanded = text.replace('\t', &" ".repeat(TAB_WIDTH));
+            writeln!(
+                out,
+                "{} {} {}",
+                self.paint("1;34", &format!("{:>gutter$}", line)),
+                bar,
+                expanded.trim_end()
+            )
+            .unwrap();
+
+            for location in locations {
+                if line < location.start.0 || line > location.end.0 {
+                    continue;
+                }
+
+                let indent = text.len() - text.trim_start().len();
+                let from = if line == location.start.0 {
+                    location.start.1
+                } else {
+                    display_width(text, text[..indent].chars().count())
+                };
+                let to = if line == location.end.0 {
+                    location.end.1
+                } else {
+                    display_width(text, text.chars().count())
+                };
+
+                let (mark, style) = if location.label.primary {
+                    ("^", severity.color())
+                } else {
+                    ("-", "1;34")
+                };
+                let mut underline = mark.repeat(to.saturating_sub(from).max(1));
+                if line == location.end.0 && !location.label.message.is_empty() {
+                    underline.push(' ');
+                    underline.push_str(&location.label.message);
+                }
+                writeln!(
+                    out,
+                    "{} {} {}{}",
+                    pad,
+                    bar,
+                    " ".repeat(from),
+                    self.paint(style, &underline)
+                )
+                .unwrap();
+            }
+        }
+    }
+}
@@ -6,12 +6,12 @@
 
 use super::*;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ConstExp {
     pub exp: Exp,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Array {
     List(Vec<Exp>, Span),
     Template(Exp, ConstExp, Span),
@@ -26,7 +26,7 @@ impl Array {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Exp {
     Exp(Box<Exp>, Span),
     Number(Number),
@@ -38,6 +38,7 @@ pub enum Exp {
     GetAddr(Box<GetAddr>),
     Deref(Box<Deref>),
     Array(Box<Array>),
+    Float(Float),
 }
 
 impl Exp {
@@ -53,18 +54,28 @@ impl Exp {
             Exp::Str(_, span) => span.clone(),
             Exp::Deref(deref) => deref.get_span(),
             Exp::Array(array) => array.get_span(),
+            Exp::Float(float) => float.span.clone(),
+        }
+    }
+
+    /// The span of the whole expression, `get_span` of an operation is its operator
+    pub fn full_span(&self) -> Span {
+        match self {
+            Exp::Unary(_, exp, span) => span.merge(&exp.full_span()),
+            Exp::Binary(lhs, _, rhs, _) => lhs.full_span().merge(&rhs.full_span()),
+            _ => self.get_span(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct LVal {
     pub ids: Vec<String>,
     pub span: Span,
     pub exp: Option<Exp>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Deref {
     DerefId(LVal, Span),
     DerefExp(Exp, Span),
@@ -83,26 +94,90 @@ impl Deref {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetAddr {
     pub lval: LVal,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// An integer literal
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Number {
-    pub num: u64,
+    /// The value, stored as a decimal string. Parsed literals are never negative, a
+    /// leading `-` is a `UnaryOp::Negative`.
+    #[serde(with = "decimal")]
+    #[schemars(with = "String")]
+    pub num: BigInt,
+    /// The type written after the digits, like the `u8` of `8u8`
+    pub suffix: Option<VTypeEnum>,
+    /// The radix the literal was written in
+    pub radix: Radix,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hex,
+}
+
+impl Radix {
+    pub fn value(self) -> u32 {
+        match self {
+            Self::Binary => 2,
+            Self::Octal => 8,
+            Self::Decimal => 10,
+            Self::Hex => 16,
+        }
+    }
+
+    /// What comes before the digits in Cara, like `0x`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Binary => "0b",
+            Self::Octal => "0o",
+            Self::Decimal => "",
+            Self::Hex => "0x",
+        }
+    }
+}
+
+mod decimal {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::BigInt;
+
+    pub fn serialize<S: Serializer>(num: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(num)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// A floating-point literal
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Float {
+    pub num: f64,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum UnaryOp {
     Positive,
     Negative,
+    /// `!`, the logical not, `true` for `false` and for integers that are zero
     Not,
+    /// `~`, flips every bit
+    BitNot,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum BinaryOp {
     Mul,
     Div,
@@ -115,4 +190,33 @@ pub enum BinaryOp {
     Ge,
     Eq,
     Neq,
+    /// `&`
+    BitAnd,
+    /// `|`
+    BitOr,
+    /// `^`
+    BitXor,
+    /// `<<`
+    Shl,
+    /// `>>`, arithmetic for signed integers and logical for unsigned ones
+    Shr,
+    /// `&&`, evaluates the right operand only if the left one is true
+    And,
+    /// `||`, evaluates the right operand only if the left one is false
+    Or,
+}
+
+impl BinaryOp {
+    /// Whether the operator is `&&` or `||`, which may skip their right operand
+    pub fn is_short_circuit(&self) -> bool {
+        matches!(self, Self::And | Self::Or)
+    }
+
+    /// Whether the operator compares its operands, giving a `bool`
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Lt | Self::Gt | Self::Le | Self::Ge | Self::Eq | Self::Neq
+        )
+    }
 }
@@ -0,0 +1,545 @@
+use super::*;
+
+/// Rewrites the AST by taking nodes by value and returning the transformed nodes.
+///
+/// Every `fold_*` method calls the matching `walk_*` method by default, and every
+/// `walk_*` method rebuilds the node from its folded children. Override `fold_*` to
+/// replace a node and call `walk_*` from it if the children should still be folded.
+pub trait Fold {
+    fn fold_comp_unit(&mut self, unit: CompUnit) -> CompUnit {
+        self.walk_comp_unit(unit)
+    }
+
+    fn walk_comp_unit(&mut self, unit: CompUnit) -> CompUnit {
+        CompUnit {
+            global_items: unit
+                .global_items
+                .into_iter()
+                .map(|item| self.fold_global_item(item))
+                .collect(),
+            span: self.fold_span(unit.span),
+            source_map: unit.source_map,
+        }
+    }
+
+    fn fold_global_item(&mut self, item: GlobalItem) -> GlobalItem {
+        self.walk_global_item(item)
+    }
+
+    fn walk_global_item(&mut self, item: GlobalItem) -> GlobalItem {
+        match item {
+            GlobalItem::ConstDecl(decl) => GlobalItem::ConstDecl(self.fold_const_decl(decl)),
+            GlobalItem::BuiltinFnCall(call) => {
+                GlobalItem::BuiltinFnCall(self.fold_builtin_function_call(call))
+            }
+            GlobalItem::InlineAsm(asm) => GlobalItem::InlineAsm(self.fold_inline_asm(asm)),
+        }
+    }
+
+    fn fold_attributes(&mut self, attr: Attributes) -> Attributes {
+        self.walk_attributes(attr)
+    }
+
+    fn walk_attributes(&mut self, attr: Attributes) -> Attributes {
+        Attributes {
+            attrs: attr.attrs,
+            span: self.fold_span(attr.span),
+        }
+    }
+
+    fn fold_const_decl(&mut self, decl: ConstDecl) -> ConstDecl {
+        self.walk_const_decl(decl)
+    }
+
+    fn walk_const_decl(&mut self, decl: ConstDecl) -> ConstDecl {
+        ConstDecl {
+            attr: decl.attr.map(|attr| self.fold_attributes(attr)),
+            id: decl.id,
+            init: self.fold_const_init_val(decl.init),
+            span: self.fold_span(decl.span),
+        }
+    }
+
+    fn fold_const_init_val(&mut self, init: ConstInitVal) -> ConstInitVal {
+        self.walk_const_init_val(init)
+    }
+
+    fn walk_const_init_val(&mut self, init: ConstInitVal) -> ConstInitVal {
+        match init {
+            ConstInitVal::Exp(exp) => ConstInitVal::Exp(self.fold_const_exp(exp)),
+            ConstInitVal::Function(func) => ConstInitVal::Function(self.fold_func_def(func)),
+            ConstInitVal::Proto(proto) => ConstInitVal::Proto(self.fold_proto_def(proto)),
+            ConstInitVal::Class(class) => ConstInitVal::Class(self.fold_class_def(class)),
+        }
+    }
+
+    fn fold_const_exp(&mut self, exp: ConstExp) -> ConstExp {
+        self.walk_const_exp(exp)
+    }
+
+    fn walk_const_exp(&mut self, exp: ConstExp) -> ConstExp {
+        ConstExp {
+            exp: self.fold_exp(exp.exp),
+        }
+    }
+
+    fn fold_func_def(&mut self, func: FuncDef) -> FuncDef {
+        self.walk_func_def(func)
+    }
+
+    fn walk_func_def(&mut self, func: FuncDef) -> FuncDef {
+        let params = func
+            .params
+            .into_iter()
+            .map(|param| self.fold_param(param))
+            .collect();
+        FuncDef {
+            func_type: self.fold_vtype(func.func_type),
+            block: self.fold_block(func.block),
+            params,
+            span: self.fold_span(func.span),
+        }
+    }
+
+    fn fold_proto_def(&mut self, proto: ProtoDef) -> ProtoDef {
+        self.walk_proto_def(proto)
+    }
+
+    fn walk_proto_def(&mut self, proto: ProtoDef) -> ProtoDef {
+        let params = proto
+            .params
+            .into_iter()
+            .map(|param| self.fold_param(param))
+            .collect();
+        ProtoDef {
+            func_type: self.fold_vtype(proto.func_type),
+            params,
+            span: self.fold_span(proto.span),
+        }
+    }
+
+    fn fold_param(&mut self, param: Param) -> Param {
+        self.walk_param(param)
+    }
+
+    fn walk_param(&mut self, param: Param) -> Param {
+        Param {
+            ty: self.fold_vtype(param.ty),
+            id: param.id,
+            span: self.fold_span(param.span),
+        }
+    }
+
+    fn fold_class_def(&mut self, class: ClassDef) -> ClassDef {
+        self.walk_class_def(class)
+    }
+
+    fn walk_class_def(&mut self, class: ClassDef) -> ClassDef {
+        ClassDef {
+            members: class
+                .members
+                .into_iter()
+                .map(|member| self.fold_class_member(member))
+                .collect(),
+            consts: class
+                .consts
+                .into_iter()
+                .map(|decl| self.fold_const_decl(decl))
+                .collect(),
+            span: self.fold_span(class.span),
+        }
+    }
+
+    fn fold_class_member(&mut self, member: ClassMember) -> ClassMember {
+        self.walk_class_member(member)
+    }
+
+    fn walk_class_member(&mut self, member: ClassMember) -> ClassMember {
+        ClassMember {
+            ty: self.fold_vtype(member.ty),
+            id: member.id,
+            span: self.fold_span(member.span),
+        }
+    }
+
+    fn fold_new_class(&mut self, class: NewClass) -> NewClass {
+        self.walk_new_class(class)
+    }
+
+    fn walk_new_class(&mut self, class: NewClass) -> NewClass {
+        NewClass {
+            class: class.class,
+            members: class
+                .members
+                .into_iter()
+                .map(|member| self.fold_new_class_member(member))
+                .collect(),
+            span: self.fold_span(class.span),
+        }
+    }
+
+    fn fold_new_class_member(&mut self, member: NewClassMember) -> NewClassMember {
+        self.walk_new_class_member(member)
+    }
+
+    fn walk_new_class_member(&mut self, member: NewClassMember) -> NewClassMember {
+        NewClassMember {
+            id: member.id,
+            val: self.fold_exp(member.val),
+            span: self.fold_span(member.span),
+        }
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        self.walk_block(block)
+    }
+
+    fn walk_block(&mut self, block: Block) -> Block {
+        Block {
+            items: block
+                .items
+                .into_iter()
+                .map(|item| self.fold_block_item(item))
+                .collect(),
+            span: self.fold_span(block.span),
+        }
+    }
+
+    fn fold_block_item(&mut self, item: BlockItem) -> BlockItem {
+        self.walk_block_item(item)
+    }
+
+    fn walk_block_item(&mut self, item: BlockItem) -> BlockItem {
+        match item {
+            BlockItem::Decl(decl) => BlockItem::Decl(self.fold_decl(decl)),
+            BlockItem::Stmt(stmt) => BlockItem::Stmt(self.fold_stmt(stmt)),
+        }
+    }
+
+    fn fold_decl(&mut self, decl: Decl) -> Decl {
+        self.walk_decl(decl)
+    }
+
+    fn walk_decl(&mut self, decl: Decl) -> Decl {
+        match decl {
+            Decl::Const(decl) => Decl::Const(self.fold_const_decl(decl)),
+            Decl::Var(decl) => Decl::Var(self.fold_var_decl(decl)),
+        }
+    }
+
+    fn fold_var_decl(&mut self, decl: VarDecl) -> VarDecl {
+        self.walk_var_decl(decl)
+    }
+
+    fn walk_var_decl(&mut self, decl: VarDecl) -> VarDecl {
+        VarDecl {
+            id: decl.id,
+            ty: self.fold_vtype(decl.ty),
+            init: self.fold_init_val(decl.init),
+            span: self.fold_span(decl.span),
+        }
+    }
+
+    fn fold_init_val(&mut self, init: InitVal) -> InitVal {
+        self.walk_init_val(init)
+    }
+
+    fn walk_init_val(&mut self, init: InitVal) -> InitVal {
+        InitVal {
+            exp: self.fold_exp(init.exp),
+        }
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        self.walk_stmt(stmt)
+    }
+
+    fn walk_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt {
+            Stmt::Assign(assign) => Stmt::Assign(self.fold_assign(assign)),
+            Stmt::Return(ret) => Stmt::Return(self.fold_return(ret)),
+            Stmt::Block(block) => Stmt::Block(self.fold_block(block)),
+            Stmt::Exp(exp) => Stmt::Exp(exp.map(|exp| self.fold_exp(exp))),
+            Stmt::If(if_stmt) => Stmt::If(self.fold_if(if_stmt)),
+            Stmt::InlineAsm(asm) => Stmt::InlineAsm(self.fold_inline_asm(asm)),
+            Stmt::While(while_stmt) => Stmt::While(self.fold_while(while_stmt)),
+            Stmt::Terminator(terminator) => Stmt::Terminator(self.fold_terminator(terminator)),
+            Stmt::For(for_stmt) => Stmt::For(self.fold_for(for_stmt)),
+        }
+    }
+
+    fn fold_assign(&mut self, assign: Assign) -> Assign {
+        self.walk_assign(assign)
+    }
+
+    fn walk_assign(&mut self, assign: Assign) -> Assign {
+        match assign {
+            Assign::WriteVar(lval, exp, span) => Assign::WriteVar(
+                self.fold_lval(lval),
+                self.fold_exp(exp),
+                self.fold_span(span),
+            ),
+            Assign::WritePtr(deref, exp, span) => Assign::WritePtr(
+                self.fold_deref(deref),
+                self.fold_exp(exp),
+                self.fold_span(span),
+            ),
+        }
+    }
+
+    fn fold_return(&mut self, ret: Return) -> Return {
+        self.walk_return(ret)
+    }
+
+    fn walk_return(&mut self, ret: Return) -> Return {
+        Return {
+            exp: ret.exp.map(|exp| self.fold_exp(exp)),
+            span: self.fold_span(ret.span),
+        }
+    }
+
+    fn fold_if(&mut self, if_stmt: If) -> If {
+        self.walk_if(if_stmt)
+    }
+
+    fn walk_if(&mut self, if_stmt: If) -> If {
+        If {
+            cond: self.fold_exp(if_stmt.cond),
+            then: self.fold_block(if_stmt.then),
+            else_then: if_stmt.else_then.map(|block| self.fold_block(block)),
+            span: self.fold_span(if_stmt.span),
+        }
+    }
+
+    fn fold_while(&mut self, while_stmt: While) -> While {
+        self.walk_while(while_stmt)
+    }
+
+    fn walk_while(&mut self, while_stmt: While) -> While {
+        While {
+            cond: self.fold_exp(while_stmt.cond),
+            then: self.fold_block(while_stmt.then),
+            span: self.fold_span(while_stmt.span),
+        }
+    }
+
+    fn fold_for(&mut self, for_stmt: For) -> For {
+        self.walk_for(for_stmt)
+    }
+
+    fn walk_for(&mut self, for_stmt: For) -> For {
+        For {
+            var: for_stmt.var,
+            start: self.fold_exp(for_stmt.start),
+            end: self.fold_exp(for_stmt.end),
+            step: self.fold_exp(for_stmt.step),
+            then: self.fold_block(for_stmt.then),
+            span: self.fold_span(for_stmt.span),
+        }
+    }
+
+    fn fold_inline_asm(&mut self, asm: InlineAsm) -> InlineAsm {
+        self.walk_inline_asm(asm)
+    }
+
+    fn walk_inline_asm(&mut self, asm: InlineAsm) -> InlineAsm {
+        InlineAsm {
+            asm: asm.asm,
+            constraints: asm
+                .constraints
+                .into_iter()
+                .map(|constraint| self.fold_asm_constraint(constraint))
+                .collect(),
+            span: self.fold_span(asm.span),
+        }
+    }
+
+    fn fold_asm_constraint(&mut self, constraint: AsmConstraint) -> AsmConstraint {
+        self.walk_asm_constraint(constraint)
+    }
+
+    fn walk_asm_constraint(&mut self, constraint: AsmConstraint) -> AsmConstraint {
+        match constraint {
+            AsmConstraint::In(id, exp, span) => {
+                AsmConstraint::In(id, self.fold_exp(exp), self.fold_span(span))
+            }
+            AsmConstraint::Out(id, lval, span) => {
+                AsmConstraint::Out(id, self.fold_lval(lval), self.fold_span(span))
+            }
+        }
+    }
+
+    fn fold_terminator(&mut self, terminator: Terminator) -> Terminator {
+        self.walk_terminator(terminator)
+    }
+
+    fn walk_terminator(&mut self, terminator: Terminator) -> Terminator {
+        match terminator {
+            Terminator::Break(span) => Terminator::Break(self.fold_span(span)),
+            Terminator::Continue(span) => Terminator::Continue(self.fold_span(span)),
+        }
+    }
+
+    fn fold_builtin_function_call(&mut self, call: BuiltinFunctionCall) -> BuiltinFunctionCall {
+        self.walk_builtin_function_call(call)
+    }
+
+    fn walk_builtin_function_call(&mut self, call: BuiltinFunctionCall) -> BuiltinFunctionCall {
+        BuiltinFunctionCall {
+            func: call.func,
+            args: call.args.into_iter().map(|arg| self.fold_exp(arg)).collect(),
+            span: self.fold_span(call.span),
+        }
+    }
+
+    fn fold_exp(&mut self, exp: Exp) -> Exp {
+        self.walk_exp(exp)
+    }
+
+    fn walk_exp(&mut self, exp: Exp) -> Exp {
+        match exp {
+            Exp::Exp(exp, span) => Exp::Exp(Box::new(self.fold_exp(*exp)), self.fold_span(span)),
+            Exp::Number(number) => Exp::Number(self.fold_number(number)),
+            Exp::LVal(lval) => Exp::LVal(Box::new(self.fold_lval(*lval))),
+            Exp::FuncCall(call) => Exp::FuncCall(self.fold_func_call(call)),
+            Exp::Str(string, span) => Exp::Str(string, self.fold_span(span)),
+            Exp::Unary(op, exp, span) => Exp::Unary(
+                self.fold_unary_op(op),
+                Box::new(self.fold_exp(*exp)),
+                self.fold_span(span),
+            ),
+            Exp::Binary(lhs, op, rhs, span) => Exp::Binary(
+                Box::new(self.fold_exp(*lhs)),
+                self.fold_binary_op(op),
+                Box::new(self.fold_exp(*rhs)),
+                self.fold_span(span),
+            ),
+            Exp::GetAddr(get_addr) => Exp::GetAddr(Box::new(self.fold_get_addr(*get_addr))),
+            Exp::Deref(deref) => Exp::Deref(Box::new(self.fold_deref(*deref))),
+            Exp::Array(array) => Exp::Array(Box::new(self.fold_array(*array))),
+            Exp::Float(float) => Exp::Float(self.fold_float(float)),
+        }
+    }
+
+    fn fold_number(&mut self, number: Number) -> Number {
+        self.walk_number(number)
+    }
+
+    fn walk_number(&mut self, number: Number) -> Number {
+        Number {
+            num: number.num,
+            suffix: number.suffix,
+            radix: number.radix,
+            span: self.fold_span(number.span),
+        }
+    }
+
+    fn fold_float(&mut self, float: Float) -> Float {
+        self.walk_float(float)
+    }
+
+    fn walk_float(&mut self, float: Float) -> Float {
+        Float {
+            num: float.num,
+            span: self.fold_span(float.span),
+        }
+    }
+
+    fn fold_lval(&mut self, lval: LVal) -> LVal {
+        self.walk_lval(lval)
+    }
+
+    fn walk_lval(&mut self, lval: LVal) -> LVal {
+        LVal {
+            ids: lval.ids,
+            exp: lval.exp.map(|exp| self.fold_exp(exp)),
+            span: self.fold_span(lval.span),
+        }
+    }
+
+    fn fold_func_call(&mut self, call: FuncCall) -> FuncCall {
+        self.walk_func_call(call)
+    }
+
+    fn walk_func_call(&mut self, call: FuncCall) -> FuncCall {
+        FuncCall {
+            ids: call.ids,
+            args: call.args.into_iter().map(|arg| self.fold_exp(arg)).collect(),
+            span: self.fold_span(call.span),
+        }
+    }
+
+    fn fold_get_addr(&mut self, get_addr: GetAddr) -> GetAddr {
+        self.walk_get_addr(get_addr)
+    }
+
+    fn walk_get_addr(&mut self, get_addr: GetAddr) -> GetAddr {
+        GetAddr {
+            lval: self.fold_lval(get_addr.lval),
+            span: self.fold_span(get_addr.span),
+        }
+    }
+
+    fn fold_deref(&mut self, deref: Deref) -> Deref {
+        self.walk_deref(deref)
+    }
+
+    fn walk_deref(&mut self, deref: Deref) -> Deref {
+        match deref {
+            Deref::DerefId(lval, span) => Deref::DerefId(self.fold_lval(lval), self.fold_span(span)),
+            Deref::DerefExp(exp, span) => Deref::DerefExp(self.fold_exp(exp), self.fold_span(span)),
+            Deref::DerefPtrExp(exp, index, span) => Deref::DerefPtrExp(
+                self.fold_exp(exp),
+                self.fold_exp(index),
+                self.fold_span(span),
+            ),
+            Deref::DerefPtr(lval, index, span) => Deref::DerefPtr(
+                self.fold_lval(lval),
+                self.fold_exp(index),
+                self.fold_span(span),
+            ),
+        }
+    }
+
+    fn fold_array(&mut self, array: Array) -> Array {
+        self.walk_array(array)
+    }
+
+    fn walk_array(&mut self, array: Array) -> Array {
+        match array {
+            Array::List(values, span) => Array::List(
+                values.into_iter().map(|value| self.fold_exp(value)).collect(),
+                self.fold_span(span),
+            ),
+            Array::Template(value, count, span) => Array::Template(
+                self.fold_exp(value),
+                self.fold_const_exp(count),
+                self.fold_span(span),
+            ),
+        }
+    }
+
+    fn fold_vtype(&mut self, vtype: VType) -> VType {
+        self.walk_vtype(vtype)
+    }
+
+    fn walk_vtype(&mut self, vtype: VType) -> VType {
+        VType {
+            ty: vtype.ty,
+            star: vtype.star,
+            span: self.fold_span(vtype.span),
+        }
+    }
+
+    fn fold_unary_op(&mut self, op: UnaryOp) -> UnaryOp {
+        op
+    }
+
+    fn fold_binary_op(&mut self, op: BinaryOp) -> BinaryOp {
+        op
+    }
+
+    fn fold_span(&mut self, span: Span) -> Span {
+        span
+    }
+}
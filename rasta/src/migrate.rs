@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+
+use rmpv::Value;
+
+use super::*;
+
+/// A step that upgrades a payload from schema version `from` to `from + 1`
+struct Migration {
+    from: u32,
+    apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// Every migration, in order. Migrations work on the decoded MessagePack value, so they
+/// don't need a copy of the old AST types.
+const MIGRATIONS: &[Migration] = &[
+    // Version 0 is the bare `CompUnit` written before the container header existed,
+    // the payload itself is the same as version 1.
+    Migration {
+        from: 0,
+        apply: |_| Ok(()),
+    },
+    // Version 2 made `Return::exp` optional. `Some(exp)` is encoded exactly like `exp`,
+    // so the old mandatory expressions decode as they are.
+    Migration {
+        from: 1,
+        apply: |_| Ok(()),
+    },
+    // Version 3 appended the byte range to `Span`. It defaults to `0..0` when missing, so
+    // older spans decode without byte offsets.
+    Migration {
+        from: 2,
+        apply: |_| Ok(()),
+    },
+    // Version 4 moved file names and source text into `CompUnit::source_map`, spans are
+    // a file id and a byte range now.
+    Migration {
+        from: 3,
+        apply: intern_spans,
+    },
+    // Version 5 added integer types of every width and `bool` to `VTypeEnum`. Variants
+    // are encoded by name, so the old types decode as they are.
+    Migration {
+        from: 4,
+        apply: |_| Ok(()),
+    },
+    // Version 6 added `f32`, `f64` and `Exp::Float`, which older data doesn't use.
+    Migration {
+        from: 5,
+        apply: |_| Ok(()),
+    },
+    // Version 7 made `Number::num` arbitrary-precision, stored as a decimal string, and
+    // added its `suffix` and `radix`.
+    Migration {
+        from: 6,
+        apply: widen_numbers,
+    },
+    // Version 8 added bitwise, shift and logical operators and split `!` into the logical
+    // `Not` and the bitwise `BitNot`. The old `Not` was bitwise on integers.
+    Migration {
+        from: 7,
+        apply: rename_not,
+    },
+];
+
+/// Upgrade a `CompUnit` payload written with schema `version` to [`SCHEMA_VERSION`]
+pub fn migrate(version: u32, payload: &mut Value) -> Result<(), SerializeError> {
+    if version > SCHEMA_VERSION {
+        return Err(SerializeError::UnsupportedVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= version) {
+        (migration.apply)(payload).map_err(|reason| SerializeError::Migrate {
+            from: migration.from,
+            reason,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// A span from before version 4, `[start, end, first line, file, bytes?]`
+struct OldSpan<'a> {
+    start: (usize, usize),
+    end: (usize, usize),
+    line: &'a str,
+    file: &'a str,
+}
+
+impl<'a> OldSpan<'a> {
+    fn parse(value: &'a Value) -> Option<Self> {
+        let fields = value.as_array()?;
+        if fields.len() != 4 && fields.len() != 5 {
+            return None;
+        }
+
+        let pos = |value: &Value| -> Option<(usize, usize)> {
+            match value.as_array()?.as_slice() {
+                [line, col] => Some((line.as_u64()? as usize, col.as_u64()? as usize)),
+                _ => None,
+            }
+        };
+        Some(Self {
+            start: pos(&fields[0])?,
+            end: pos(&fields[1])?,
+            line: fields[2].as_str()?.trim_end_matches(['\n', '\r']),
+            file: fields[3].as_str()?,
+        })
+    }
+}
+
+/// The files of the old spans, with the source lines the spans started on
+#[derive(Default)]
+struct OldFiles {
+    files: Vec<(String, BTreeMap<usize, String>)>,
+}
+
+impl OldFiles {
+    fn collect(&mut self, value: &Value) {
+        if let Some(span) = OldSpan::parse(value) {
+            if span.start.0 == 0 {
+                return;
+            }
+            let id = match self.files.iter().position(|(name, _)| name == span.file) {
+                Some(id) => id,
+                None => {
+                    self.files.push((span.file.into(), BTreeMap::new()));
+                    self.files.len() - 1
+                }
+            };
+            self.files[id].1.insert(span.start.0, span.line.into());
+            return;
+        }
+
+        match value {
+            Value::Array(values) => values.iter().for_each(|value| self.collect(value)),
+            Value::Map(entries) => entries.iter().for_each(|(_, value)| self.collect(value)),
+            _ => {}
+        }
+    }
+}
+
+/// Rebuild what is known of every source file from the lines stored in the old spans, and
+/// point the spans into it. Lines no span started on are left empty.
+fn intern_spans(payload: &mut Value) -> Result<(), String> {
+    let mut files = OldFiles::default();
+    files.collect(payload);
+
+    let mut map = SourceMap::new();
+    for (name, lines) in files.files {
+        let count = lines.keys().last().copied().unwrap_or(0);
+        let src = (1..=count)
+            .map(|line| lines.get(&line).map_or("", String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+        map.add_file(name, src);
+    }
+
+    replace_spans(payload, &map)?;
+
+    let Value::Array(unit) = payload else {
+        return Err("the payload is not a CompUnit".into());
+    };
+    unit.push(to_value(&map)?);
+
+    Ok(())
+}
+
+/// Encode a value of the current schema the way the payload would contain it
+fn to_value<T: serde::Serialize>(value: &T) -> Result<Value, String> {
+    let data = rmp_serde::to_vec(value).map_err(|err| err.to_string())?;
+    rmpv::decode::read_value(&mut data.as_slice()).map_err(|err| err.to_string())
+}
+
+fn replace_spans(value: &mut Value, map: &SourceMap) -> Result<(), String> {
+    if let Some(span) = OldSpan::parse(value) {
+        let file = map.find_file(span.file).filter(|_| span.start.0 != 0);
+        let span = match file {
+            Some(id) => {
+                let file = map.file(id).unwrap();
+                let offset = |(line, col): (usize, usize)| {
+                    let start = file.line_offset(line).unwrap_or(file.src().len());
+                    let text = file.line(line).unwrap_or("");
+                    let col = text
+                        .char_indices()
+                        .nth(col.saturating_sub(1))
+                        .map_or(text.len(), |(i, _)| i);
+                    start + col
+                };
+                let end = offset(span.end).max(offset(span.start));
+                Span::new(id, offset(span.start)..end)
+            }
+            None => Span::dummy(),
+        };
+        *value = to_value(&span)?;
+        return Ok(());
+    }
+
+    match value {
+        Value::Array(values) => values
+            .iter_mut()
+            .try_for_each(|value| replace_spans(value, map)),
+        Value::Map(entries) => entries
+            .iter_mut()
+            .try_for_each(|(_, value)| replace_spans(value, map)),
+        _ => Ok(()),
+    }
+}
+
+/// Rewrite every `Exp::Number` from `[num, span]` to `["num", suffix, radix, span]`
+fn widen_numbers(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::Map(entries) if entries.len() == 1 && entries[0].0.as_str() == Some("Number") => {
+            let fields = match &mut entries[0].1 {
+                Value::Array(fields) if fields.len() == 2 => fields,
+                _ => return Err("a Number is not `[num, span]`".into()),
+            };
+            let num = fields[0].as_u64().ok_or("a Number is not a u64")?;
+            let span = fields.pop().unwrap();
+            *fields = vec![
+                Value::from(num.to_string()),
+                Value::Nil,
+                to_value(&Radix::Decimal)?,
+                span,
+            ];
+            Ok(())
+        }
+        Value::Array(values) => values.iter_mut().try_for_each(widen_numbers),
+        Value::Map(entries) => entries
+            .iter_mut()
+            .try_for_each(|(_, value)| widen_numbers(value)),
+        _ => Ok(()),
+    }
+}
+
+/// Turn the `Not` of every `Exp::Unary` into a `BitNot`
+fn rename_not(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::Map(entries) if entries.len() == 1 && entries[0].0.as_str() == Some("Unary") => {
+            let Value::Array(fields) = &mut entries[0].1 else {
+                return Err("a Unary is not an array".into());
+            };
+            if fields.first().and_then(Value::as_str) == Some("Not") {
+                fields[0] = Value::from("BitNot");
+            }
+            fields.iter_mut().try_for_each(rename_not)
+        }
+        Value::Array(values) => values.iter_mut().try_for_each(rename_not),
+        Value::Map(entries) => entries
+            .iter_mut()
+            .try_for_each(|(_, value)| rename_not(value)),
+        _ => Ok(()),
+    }
+}
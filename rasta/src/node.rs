@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use super::*;
+
+/// Identifies an `Exp`, `Stmt`, `Decl`, `ConstDecl` or `LVal` of a [`CompUnit`]
+///
+/// Ids are numbered in the order a [`Visitor`] reaches the nodes, parents before their
+/// children, so an unchanged tree gets the same ids every time, also after being
+/// serialized and read back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NodeKind {
+    Exp,
+    Stmt,
+    Decl,
+    ConstDecl,
+    LVal,
+}
+
+/// The [`NodeId`]s of the nodes of a [`CompUnit`]
+///
+/// Nodes are looked up by address, so the unit stays borrowed as long as the ids are
+/// around. Passing a node of another tree, even a clone, finds nothing.
+#[derive(Debug)]
+pub struct NodeIds<'a> {
+    ids: HashMap<(NodeKind, usize), NodeId>,
+    unit: PhantomData<&'a CompUnit>,
+}
+
+impl<'a> NodeIds<'a> {
+    /// Number the nodes of `unit`
+    pub fn new(unit: &'a CompUnit) -> Self {
+        let mut ids = Self {
+            ids: HashMap::new(),
+            unit: PhantomData,
+        };
+        ids.visit_comp_unit(unit);
+        ids
+    }
+
+    /// How many nodes got an id, all ids are below this
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    fn get<T>(&self, kind: NodeKind, node: &T) -> Option<NodeId> {
+        self.ids.get(&(kind, node as *const T as usize)).copied()
+    }
+
+    fn assign<T>(&mut self, kind: NodeKind, node: &T) {
+        let id = NodeId(self.ids.len() as u32);
+        self.ids.insert((kind, node as *const T as usize), id);
+    }
+
+    pub fn exp(&self, exp: &Exp) -> Option<NodeId> {
+        self.get(NodeKind::Exp, exp)
+    }
+
+    pub fn stmt(&self, stmt: &Stmt) -> Option<NodeId> {
+        self.get(NodeKind::Stmt, stmt)
+    }
+
+    pub fn decl(&self, decl: &Decl) -> Option<NodeId> {
+        self.get(NodeKind::Decl, decl)
+    }
+
+    pub fn const_decl(&self, decl: &ConstDecl) -> Option<NodeId> {
+        self.get(NodeKind::ConstDecl, decl)
+    }
+
+    pub fn lval(&self, lval: &LVal) -> Option<NodeId> {
+        self.get(NodeKind::LVal, lval)
+    }
+}
+
+impl Visitor for NodeIds<'_> {
+    fn visit_exp(&mut self, exp: &Exp) {
+        self.assign(NodeKind::Exp, exp);
+        self.walk_exp(exp)
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.assign(NodeKind::Stmt, stmt);
+        self.walk_stmt(stmt)
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        self.assign(NodeKind::Decl, decl);
+        self.walk_decl(decl)
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        self.assign(NodeKind::ConstDecl, decl);
+        self.walk_const_decl(decl)
+    }
+
+    fn visit_lval(&mut self, lval: &LVal) {
+        self.assign(NodeKind::LVal, lval);
+        self.walk_lval(lval)
+    }
+}
+
+/// A side table storing a `T` for some of the nodes of a [`CompUnit`], like the results
+/// of an analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeMap<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value` for `id`, returning the value it replaces
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        if self.values.len() <= id.index() {
+            self.values.resize_with(id.index() + 1, || None);
+        }
+        self.values[id.index()].replace(value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(id.index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.values.get_mut(id.index())?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.values.get_mut(id.index())?.take()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// The stored values, ordered by id
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| Some((NodeId(i as u32), value.as_ref()?)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|value| value.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(Option::is_none)
+    }
+}
+
+impl<T> Index<NodeId> for NodeMap<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        self.get(id).expect("no value for the node")
+    }
+}
+
+impl<T> IndexMut<NodeId> for NodeMap<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut T {
+        self.get_mut(id).expect("no value for the node")
+    }
+}
+
+impl<T> FromIterator<(NodeId, T)> for NodeMap<T> {
+    fn from_iter<I: IntoIterator<Item = (NodeId, T)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (id, value) in iter {
+            map.insert(id, value);
+        }
+        map
+    }
+}
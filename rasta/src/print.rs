@@ -0,0 +1,614 @@
+//! Turns the AST back into Cara source, the syntax of `cara/cara.pest`.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use num_bigint::Sign;
+
+use super::*;
+
+/// Print the given AST as Cara source
+pub fn unparse(unit: &CompUnit) -> String {
+    let mut printer = Printer::new();
+    printer.print_comp_unit(unit);
+    printer.finish()
+}
+
+/// Source text the AST does not keep, reinserted by a formatter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trivia {
+    /// A comment including its delimiters, `trailing` if code precedes it on its line
+    Comment { text: String, trailing: bool },
+    /// An empty line, runs of them are printed as one
+    BlankLine,
+}
+
+/// Writes AST nodes as Cara source, four spaces per indentation level
+#[derive(Debug, Default)]
+pub struct Printer {
+    out: String,
+    indent: usize,
+    trivia: VecDeque<(usize, Trivia)>,
+    blank: bool,
+    source: Option<String>,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A printer that emits the given trivia, keyed by byte offset into the
+    /// source the printed AST was parsed from, before the items that follow it. Block
+    /// comments inside an expression are emitted before the operand that follows them.
+    pub fn with_trivia(mut trivia: Vec<(usize, Trivia)>) -> Self {
+        trivia.sort_by_key(|(offset, _)| *offset);
+        Self {
+            trivia: trivia.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Print literals as they are written in `source`, the source the printed AST was
+    /// parsed from, rather than in a normal form
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    /// The source printed so far
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Print the source text of `span` if the source is known, returning whether it was
+    fn write_source(&mut self, span: &Span) -> bool {
+        let text = self
+            .source
+            .as_deref()
+            .filter(|_| !span.is_dummy())
+            .and_then(|source| source.get(span.byte_range()));
+        match text {
+            Some(text) => {
+                self.out.push_str(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn newline(&mut self) {
+        if self.out.is_empty() {
+            return;
+        }
+        if std::mem::take(&mut self.blank) {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    /// Emit the trivia found before `offset`, always followed by a newline
+    fn flush_trivia(&mut self, offset: usize) {
+        while self.trivia.front().is_some_and(|(at, _)| *at < offset) {
+            match self.trivia.pop_front().unwrap().1 {
+                Trivia::BlankLine => {
+                    if !self.out.is_empty() && !self.out.ends_with('{') {
+                        self.blank = true;
+                    }
+                }
+                Trivia::Comment { text, trailing } => {
+                    if trailing && !self.out.is_empty() {
+                        self.write(" ");
+                    } else {
+                        self.newline();
+                    }
+                    self.write(&text);
+                }
+            }
+        }
+    }
+
+    /// Emit the block comments found before `offset` in place, so a comment inside an
+    /// expression stays before the code that follows it rather than moving past the end
+    /// of the statement
+    fn flush_inline_comments(&mut self, offset: usize) {
+        while let Some((at, Trivia::Comment { text, .. })) = self.trivia.front() {
+            if *at >= offset || !text.starts_with("/*") {
+                break;
+            }
+            let text = text.clone();
+            self.trivia.pop_front();
+            self.write(&text);
+            self.write(" ");
+        }
+    }
+
+    /// Close a block opened with `{`, emitting the trivia left inside it
+    fn close_block(&mut self, end: usize) {
+        self.flush_trivia(end);
+        self.blank = false;
+        self.indent -= 1;
+        if !self.out.ends_with('{') {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    fn print_list<T>(&mut self, items: &[T], mut print: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i != 0 {
+                self.write(", ");
+            }
+            print(self, item);
+        }
+    }
+
+    pub fn print_comp_unit(&mut self, unit: &CompUnit) {
+        for (i, item) in unit.global_items.iter().enumerate() {
+            // Top level items are always separated by a blank line
+            self.blank = i != 0;
+            self.flush_trivia(item.span().byte_range().start);
+            self.newline();
+            self.print_global_item(item);
+        }
+        self.flush_trivia(usize::MAX);
+        if !self.out.is_empty() {
+            self.write("\n");
+        }
+    }
+
+    pub fn print_global_item(&mut self, item: &GlobalItem) {
+        match item {
+            GlobalItem::ConstDecl(decl) => self.print_const_decl(decl),
+            GlobalItem::BuiltinFnCall(call) => self.print_builtin_function_call(call),
+            GlobalItem::InlineAsm(asm) => self.print_inline_asm(asm),
+        }
+    }
+
+    pub fn print_attributes(&mut self, attr: &Attributes) {
+        self.write("#[");
+        self.write(&attr.attrs.join(", "));
+        self.write("]");
+    }
+
+    pub fn print_const_decl(&mut self, decl: &ConstDecl) {
+        if let Some(attr) = &decl.attr {
+            self.print_attributes(attr);
+            self.newline();
+        }
+        write!(self.out, "const {} = ", decl.id).unwrap();
+        self.print_const_init_val(&decl.init);
+        self.write(";");
+    }
+
+    pub fn print_const_init_val(&mut self, init: &ConstInitVal) {
+        match init {
+            ConstInitVal::Exp(exp) => self.print_exp(&exp.exp),
+            ConstInitVal::Function(func) => self.print_func_def(func),
+            ConstInitVal::Proto(proto) => self.print_proto_def(proto),
+            ConstInitVal::Class(class) => self.print_class_def(class),
+        }
+    }
+
+    fn print_signature(&mut self, params: &[Param], func_type: &VType) {
+        self.write("fn (");
+        self.print_list(params, Self::print_param);
+        self.write(") -> ");
+        self.print_vtype(func_type);
+    }
+
+    pub fn print_func_def(&mut self, func: &FuncDef) {
+        self.print_signature(&func.params, &func.func_type);
+        self.write(" ");
+        self.print_block(&func.block);
+    }
+
+    pub fn print_proto_def(&mut self, proto: &ProtoDef) {
+        self.print_signature(&proto.params, &proto.func_type);
+    }
+
+    pub fn print_param(&mut self, param: &Param) {
+        write!(self.out, "arg {}: ", param.id).unwrap();
+        self.print_vtype(&param.ty);
+    }
+
+    pub fn print_class_def(&mut self, class: &ClassDef) {
+        self.write("class {");
+        self.indent += 1;
+        for member in class.members.iter() {
+            self.flush_trivia(member.span.byte_range().start);
+            self.newline();
+            write!(self.out, "{}: ", member.id).unwrap();
+            self.print_vtype(&member.ty);
+            self.write(",");
+        }
+        for decl in class.consts.iter() {
+            self.flush_trivia(decl.span.byte_range().start);
+            self.newline();
+            self.print_const_decl(decl);
+        }
+        self.close_block(class.span.byte_range().end);
+    }
+
+    pub fn print_new_class(&mut self, class: &NewClass) {
+        write!(self.out, "{} {{", class.class).unwrap();
+        if !class.members.is_empty() {
+            self.write(" ");
+            self.print_list(&class.members, |printer, member| {
+                write!(printer.out, "{}: ", member.id).unwrap();
+                printer.print_exp(&member.val);
+            });
+            self.write(" ");
+        }
+        self.write("}");
+    }
+
+    /// Print `number` in its radix, the digits of hex literals in upper case
+    pub fn print_number(&mut self, number: &Number) {
+        if self.write_source(&number.span) {
+            return;
+        }
+        if number.num.sign() == Sign::Minus {
+            self.write("-");
+        }
+        self.write(number.radix.prefix());
+        let magnitude = number.num.magnitude();
+        match number.radix {
+            Radix::Binary => write!(self.out, "{:b}", magnitude),
+            Radix::Octal => write!(self.out, "{:o}", magnitude),
+            Radix::Decimal => write!(self.out, "{}", magnitude),
+            Radix::Hex => write!(self.out, "{:X}", magnitude),
+        }
+        .unwrap();
+        if let Some(suffix) = &number.suffix {
+            self.print_vtype_enum(suffix);
+        }
+    }
+
+    /// Print a float literal that reads back as `num`. Infinities are printed as a literal
+    /// too large for `f64`, which is what they are parsed from, and NaN, which has no
+    /// literal, as the division that gives it.
+    pub fn print_float(&mut self, num: f64) {
+        if num.is_nan() {
+            self.write("(0.0 / 0.0)");
+        } else if num.is_infinite() {
+            self.write(if num < 0.0 { "-1e309" } else { "1e309" });
+        } else {
+            // `Debug` keeps the `.0` of whole numbers, so the literal stays a float
+            write!(self.out, "{:?}", num).unwrap();
+        }
+    }
+
+    pub fn print_vtype(&mut self, vtype: &VType) {
+        self.print_vtype_enum(&vtype.ty);
+        for _ in 0..vtype.star {
+            self.write("*");
+        }
+    }
+
+    pub fn print_vtype_enum(&mut self, ty: &VTypeEnum) {
+        match ty {
+            VTypeEnum::Void => self.write("void"),
+            VTypeEnum::Bool => self.write("bool"),
+            VTypeEnum::F32 => self.write("f32"),
+            VTypeEnum::F64 => self.write("f64"),
+            VTypeEnum::Others(name) => self.write(name),
+            ty => {
+                let sign = if ty.is_signed() { "i" } else { "u" };
+                write!(self.out, "{}{}", sign, ty.bit_width().unwrap()).unwrap();
+            }
+        }
+    }
+
+    pub fn print_block(&mut self, block: &Block) {
+        self.write("{");
+        self.indent += 1;
+        for item in block.items.iter() {
+            if let Some(span) = item.span() {
+                self.flush_trivia(span.byte_range().start);
+            }
+            self.newline();
+            self.print_block_item(item);
+        }
+        self.close_block(block.span.byte_range().end);
+    }
+
+    pub fn print_block_item(&mut self, item: &BlockItem) {
+        match item {
+            BlockItem::Decl(Decl::Const(decl)) => self.print_const_decl(decl),
+            BlockItem::Decl(Decl::Var(decl)) => self.print_var_decl(decl),
+            BlockItem::Stmt(stmt) => self.print_stmt(stmt),
+        }
+    }
+
+    pub fn print_var_decl(&mut self, decl: &VarDecl) {
+        write!(self.out, "var {}: ", decl.id).unwrap();
+        self.print_vtype(&decl.ty);
+        self.write(" = ");
+        self.print_exp(&decl.init.exp);
+        self.write(";");
+    }
+
+    pub fn print_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assign(assign) => {
+                match assign {
+                    Assign::WriteVar(lval, exp, _) => {
+                        self.print_lval(lval);
+                        self.write(" = ");
+                        self.print_exp(exp);
+                    }
+                    Assign::WritePtr(deref, exp, _) => {
+                        self.print_deref(deref);
+                        self.write(" = ");
+                        self.print_exp(exp);
+                    }
+                }
+                self.write(";");
+            }
+            Stmt::Return(ret) => {
+                self.write("return");
+                if let Some(exp) = &ret.exp {
+                    self.write(" ");
+                    self.print_exp(exp);
+                }
+                self.write(";");
+            }
+            Stmt::Block(block) => self.print_block(block),
+            Stmt::Exp(exp) => {
+                if let Some(exp) = exp {
+                    self.print_exp(exp);
+                }
+                self.write(";");
+            }
+            Stmt::If(if_stmt) => {
+                self.write("if ");
+                self.print_exp(&if_stmt.cond);
+                self.write(" ");
+                self.print_block(&if_stmt.then);
+                if let Some(else_then) = &if_stmt.else_then {
+                    self.write(" else ");
+                    self.print_block(else_then);
+                }
+            }
+            Stmt::InlineAsm(asm) => self.print_inline_asm(asm),
+            Stmt::While(while_stmt) => {
+                self.write("while ");
+                self.print_exp(&while_stmt.cond);
+                self.write(" ");
+                self.print_block(&while_stmt.then);
+            }
+            Stmt::Terminator(Terminator::Break(_)) => self.write("break;"),
+            Stmt::Terminator(Terminator::Continue(_)) => self.write("continue;"),
+            Stmt::For(for_stmt) => {
+                write!(self.out, "for {} in (", for_stmt.var).unwrap();
+                self.print_exp(&for_stmt.start);
+                self.write(", ");
+                self.print_exp(&for_stmt.end);
+                self.write(", ");
+                self.print_exp(&for_stmt.step);
+                self.write(") ");
+                self.print_block(&for_stmt.then);
+            }
+        }
+    }
+
+    pub fn print_inline_asm(&mut self, asm: &InlineAsm) {
+        self.write("asm(");
+        self.print_string(&asm.asm);
+        for constraint in asm.constraints.iter() {
+            self.write(", ");
+            match constraint {
+                AsmConstraint::In(id, exp, _) => {
+                    write!(self.out, "in {} = ", id).unwrap();
+                    self.print_exp(exp);
+                }
+                AsmConstraint::Out(id, lval, _) => {
+                    write!(self.out, "out {} = ", id).unwrap();
+                    self.print_lval(lval);
+                }
+            }
+        }
+        self.write(");");
+    }
+
+    pub fn print_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        let name = match call.func {
+            BuiltinFunction::Import => "import",
+            BuiltinFunction::Module => "module",
+            BuiltinFunction::DoMagic => "do_magic",
+            BuiltinFunction::FirstModule => "first_module",
+        };
+        write!(self.out, "[{}](", name).unwrap();
+        self.print_list(&call.args, Self::print_exp);
+        self.write(")");
+    }
+
+    pub fn print_exp(&mut self, exp: &Exp) {
+        let span = exp.full_span();
+        if !span.is_dummy() {
+            self.flush_inline_comments(span.byte_range().start);
+        }
+        match exp {
+            Exp::Exp(exp, _) => {
+                self.write("(");
+                self.print_exp(exp);
+                self.write(")");
+            }
+            Exp::Number(number) => self.print_number(number),
+            Exp::Float(float) => {
+                if !self.write_source(&float.span) {
+                    self.print_float(float.num);
+                }
+            }
+            Exp::LVal(lval) => self.print_lval(lval),
+            Exp::FuncCall(call) => {
+                self.write(&call.ids.join("."));
+                self.write("(");
+                self.print_list(&call.args, Self::print_exp);
+                self.write(")");
+            }
+            Exp::Str(string, _) => self.print_string(string),
+            Exp::Unary(op, exp, _) => {
+                self.write(unary_symbol(op));
+                self.print_operand(exp, matches!(exp.as_ref(), Exp::Binary(..)));
+            }
+            Exp::Binary(lhs, op, rhs, _) => {
+                let prec = binary_precedence(op);
+                self.print_operand(lhs, exp_precedence(lhs) < prec);
+                write!(self.out, " {} ", binary_symbol(op)).unwrap();
+                // Every operator is left associative, so an equal right operand needs parens
+                self.print_operand(rhs, exp_precedence(rhs) <= prec);
+            }
+            Exp::GetAddr(get_addr) => {
+                self.write("&");
+                self.print_lval(&get_addr.lval);
+            }
+            Exp::Deref(deref) => self.print_deref(deref),
+            Exp::Array(array) => match array.as_ref() {
+                Array::List(values, _) => {
+                    self.write("[");
+                    self.print_list(values, Self::print_exp);
+                    self.write("]");
+                }
+                Array::Template(value, count, _) => {
+                    self.write("[");
+                    self.print_exp(value);
+                    self.write("; ");
+                    self.print_exp(&count.exp);
+                    self.write("]");
+                }
+            },
+        }
+    }
+
+    fn print_operand(&mut self, exp: &Exp, parens: bool) {
+        if parens {
+            self.write("(");
+            self.print_exp(exp);
+            self.write(")");
+        } else {
+            self.print_exp(exp);
+        }
+    }
+
+    pub fn print_lval(&mut self, lval: &LVal) {
+        self.write(&lval.ids.join("."));
+        if let Some(exp) = &lval.exp {
+            self.write("[");
+            self.print_exp(exp);
+            self.write("]");
+        }
+    }
+
+    pub fn print_deref(&mut self, deref: &Deref) {
+        match deref {
+            Deref::DerefId(lval, _) => {
+                self.write("*");
+                self.print_lval(lval);
+            }
+            Deref::DerefExp(exp, _) => {
+                self.write("*(");
+                self.print_exp(exp);
+                self.write(")");
+            }
+            Deref::DerefPtrExp(exp, index, _) => {
+                self.write("(");
+                self.print_exp(exp);
+                self.write(")[");
+                self.print_exp(index);
+                self.write("]");
+            }
+            Deref::DerefPtr(lval, index, _) => {
+                self.print_lval(lval);
+                self.write("[");
+                self.print_exp(index);
+                self.write("]");
+            }
+        }
+    }
+
+    fn print_string(&mut self, string: &str) {
+        self.write("\"");
+        for c in string.chars() {
+            match c {
+                '"' => self.write("\\\""),
+                '\\' => self.write("\\\\"),
+                '\n' => self.write("\\n"),
+                '\r' => self.write("\\r"),
+                '\t' => self.write("\\t"),
+                c if c.is_control() => write!(self.out, "\\u{:04x}", c as u32).unwrap(),
+                c => self.out.push(c),
+            }
+        }
+        self.write("\"");
+    }
+}
+
+pub(crate) fn unary_symbol(op: &UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Positive => "+",
+        UnaryOp::Negative => "-",
+        UnaryOp::Not => "!",
+        UnaryOp::BitNot => "~",
+    }
+}
+
+pub(crate) fn binary_symbol(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Lt => "<",
+        BinaryOp::Gt => ">",
+        BinaryOp::Le => "<=",
+        BinaryOp::Ge => ">=",
+        BinaryOp::Eq => "==",
+        BinaryOp::Neq => "!=",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Shl => "<<",
+        BinaryOp::Shr => ">>",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
+
+/// How tightly an operator binds, matching the Pratt parser of the Cara grammar
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Or => 1,
+        BinaryOp::And => 2,
+        BinaryOp::Lt
+        | BinaryOp::Gt
+        | BinaryOp::Le
+        | BinaryOp::Ge
+        | BinaryOp::Eq
+        | BinaryOp::Neq => 3,
+        BinaryOp::BitOr => 4,
+        BinaryOp::BitXor => 5,
+        BinaryOp::BitAnd => 6,
+        BinaryOp::Shl | BinaryOp::Shr => 7,
+        BinaryOp::Add | BinaryOp::Sub => 8,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 9,
+    }
+}
+
+/// The precedence of an expression used as an operand, everything but a binary
+/// expression is atomic
+fn exp_precedence(exp: &Exp) -> u8 {
+    match exp {
+        Exp::Binary(_, op, _, _) => binary_precedence(op),
+        _ => u8::MAX,
+    }
+}
@@ -1,26 +1,49 @@
 use super::*;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CompUnit {
     pub global_items: Vec<GlobalItem>,
     pub span: Span,
+    /// The files every span in the unit points into
+    pub source_map: SourceMap,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum GlobalItem {
     ConstDecl(ConstDecl),
     BuiltinFnCall(BuiltinFunctionCall),
     InlineAsm(InlineAsm),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Block {
     pub items: Vec<BlockItem>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum BlockItem {
     Decl(Decl),
     Stmt(Stmt),
 }
+
+impl GlobalItem {
+    pub fn span(&self) -> Span {
+        match self {
+            GlobalItem::ConstDecl(decl) => decl.span.clone(),
+            GlobalItem::BuiltinFnCall(call) => call.span.clone(),
+            GlobalItem::InlineAsm(asm) => asm.span.clone(),
+        }
+    }
+}
+
+impl BlockItem {
+    /// The span of the item, `None` for an empty statement
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            BlockItem::Decl(Decl::Const(decl)) => Some(decl.span.clone()),
+            BlockItem::Decl(Decl::Var(decl)) => Some(decl.span.clone()),
+            BlockItem::Stmt(stmt) => stmt.span(),
+        }
+    }
+}
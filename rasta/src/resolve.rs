@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+
+use super::*;
+
+/// Identifies a [`Symbol`] of a [`Resolution`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SymbolId(u32);
+
+impl SymbolId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The definition a [`Symbol`] comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// A `ConstDecl`, global or local
+    Const(NodeId),
+    /// The `Decl` of a `VarDecl`
+    Var(NodeId),
+    /// The `index`th parameter of the function or proto declared by the `ConstDecl` `func`
+    Param { func: NodeId, index: usize },
+    /// The loop variable of the `For` statement
+    ForVar(NodeId),
+}
+
+/// A name defined somewhere in a [`CompUnit`]
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub span: Span,
+}
+
+/// The symbols of a [`CompUnit`] and what every name in it refers to
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub symbols: Vec<Symbol>,
+    /// The symbol used by every resolved `LVal` and `Exp::FuncCall`
+    pub uses: NodeMap<SymbolId>,
+    /// Undefined and duplicate names
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolution {
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.index()]
+    }
+
+    /// Every symbol with its id, in the order of definition
+    pub fn iter(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| (SymbolId(i as u32), symbol))
+    }
+
+    /// The symbol the `LVal` or `Exp::FuncCall` with the given id refers to
+    pub fn lookup(&self, id: NodeId) -> Option<&Symbol> {
+        self.uses.get(id).map(|symbol| self.symbol(*symbol))
+    }
+
+    /// Whether every name could be resolved
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Resolve the names used in `unit` to their definitions.
+///
+/// Global constants are visible everywhere. Every function, `Block`, class and `For` loop
+/// opens a scope. Inside a scope a name is visible after its definition, a constant
+/// already in its own initializer, so local functions can be recursive. Only the first of
+/// the `ids` of a path is resolved, the rest are members.
+pub fn resolve(unit: &CompUnit, ids: &NodeIds) -> Resolution {
+    let mut resolver = Resolver {
+        ids,
+        scopes: Vec::new(),
+        func: None,
+        resolution: Resolution::default(),
+    };
+    resolver.visit_comp_unit(unit);
+    resolver.resolution
+}
+
+struct Resolver<'a, 'b> {
+    ids: &'a NodeIds<'b>,
+    scopes: Vec<HashMap<String, SymbolId>>,
+    /// The `ConstDecl` of the function whose params are being defined
+    func: Option<NodeId>,
+    resolution: Resolution,
+}
+
+impl Resolver<'_, '_> {
+    fn define(&mut self, name: &str, kind: SymbolKind, span: &Span) {
+        let id = SymbolId(self.resolution.symbols.len() as u32);
+        let scope = self.scopes.last_mut().unwrap();
+        if let Some(first) = scope.get(name) {
+            let first = &self.resolution.symbols[first.index()];
+            self.resolution.diagnostics.push(
+                Diagnostic::error(format!("`{}` is defined more than once", name))
+                    .with_primary(span.clone(), "defined again here")
+                    .with_secondary(first.span.clone(), "first defined here"),
+            );
+            return;
+        }
+        scope.insert(name.to_string(), id);
+        self.resolution.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            span: span.clone(),
+        });
+    }
+
+    fn use_name(&mut self, node: Option<NodeId>, ids: &[String], span: &Span) {
+        let Some(name) = ids.first() else {
+            return;
+        };
+        let symbol = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied());
+        match (symbol, node) {
+            (Some(symbol), Some(node)) => {
+                self.resolution.uses.insert(node, symbol);
+            }
+            (Some(_), None) => {}
+            (None, _) => self.resolution.diagnostics.push(
+                Diagnostic::error(format!("cannot find `{}` in this scope", name))
+                    .with_primary(span.clone(), "not found"),
+            ),
+        }
+    }
+
+    fn define_params(&mut self, params: &[Param]) {
+        let Some(func) = self.func.take() else {
+            return;
+        };
+        for (index, param) in params.iter().enumerate() {
+            self.define(&param.id, SymbolKind::Param { func, index }, &param.span);
+        }
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl Visitor for Resolver<'_, '_> {
+    fn visit_comp_unit(&mut self, unit: &CompUnit) {
+        self.scoped(|this| {
+            for item in unit.global_items.iter() {
+                if let GlobalItem::ConstDecl(decl) = item {
+                    if let Some(id) = this.ids.const_decl(decl) {
+                        this.define(&decl.id, SymbolKind::Const(id), &decl.span);
+                    }
+                }
+            }
+            this.walk_comp_unit(unit);
+        });
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        let id = self.ids.const_decl(decl);
+        // Global constants are defined up front by `visit_comp_unit`
+        if self.scopes.len() > 1 {
+            if let Some(id) = id {
+                self.define(&decl.id, SymbolKind::Const(id), &decl.span);
+            }
+        }
+        let func = std::mem::replace(&mut self.func, id);
+        self.walk_const_decl(decl);
+        self.func = func;
+    }
+
+    fn visit_func_def(&mut self, func: &FuncDef) {
+        self.scoped(|this| {
+            this.define_params(&func.params);
+            this.walk_func_def(func);
+        });
+    }
+
+    fn visit_proto_def(&mut self, proto: &ProtoDef) {
+        self.scoped(|this| {
+            this.define_params(&proto.params);
+            this.walk_proto_def(proto);
+        });
+    }
+
+    fn visit_class_def(&mut self, class: &ClassDef) {
+        self.scoped(|this| this.walk_class_def(class));
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.scoped(|this| this.walk_block(block));
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Const(decl) => self.visit_const_decl(decl),
+            Decl::Var(var) => {
+                // The initializer can't see the variable itself
+                self.visit_var_decl(var);
+                if let Some(id) = self.ids.decl(decl) {
+                    self.define(&var.id, SymbolKind::Var(id), &var.span);
+                }
+            }
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        let Stmt::For(for_stmt) = stmt else {
+            return self.walk_stmt(stmt);
+        };
+        self.visit_exp(&for_stmt.start);
+        self.visit_exp(&for_stmt.end);
+        self.visit_exp(&for_stmt.step);
+        self.scoped(|this| {
+            if let Some(id) = this.ids.stmt(stmt) {
+                this.define(&for_stmt.var, SymbolKind::ForVar(id), &for_stmt.span);
+            }
+            this.visit_block(&for_stmt.then);
+        });
+    }
+
+    fn visit_exp(&mut self, exp: &Exp) {
+        if let Exp::FuncCall(call) = exp {
+            self.use_name(self.ids.exp(exp), &call.ids, &call.span);
+        }
+        self.walk_exp(exp)
+    }
+
+    fn visit_lval(&mut self, lval: &LVal) {
+        self.use_name(self.ids.lval(lval), &lval.ids, &lval.span);
+        self.walk_lval(lval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::*;
+
+    /// Every name used in a unit, with the index of the symbol it resolves to
+    struct Uses<'a, 'b> {
+        ids: &'a NodeIds<'b>,
+        resolution: &'a Resolution,
+        uses: Vec<(String, Option<usize>)>,
+    }
+
+    impl Uses<'_, '_> {
+        fn record(&mut self, node: Option<NodeId>, ids: &[String]) {
+            let symbol = node
+                .and_then(|node| self.resolution.uses.get(node))
+                .map(|symbol| symbol.index());
+            self.uses.push((ids[0].clone(), symbol));
+        }
+    }
+
+    impl Visitor for Uses<'_, '_> {
+        fn visit_exp(&mut self, exp: &Exp) {
+            if let Exp::FuncCall(call) = exp {
+                self.record(self.ids.exp(exp), &call.ids);
+            }
+            self.walk_exp(exp)
+        }
+
+        fn visit_lval(&mut self, lval: &LVal) {
+            self.record(self.ids.lval(lval), &lval.ids);
+            self.walk_lval(lval)
+        }
+    }
+
+    fn kind(kind: SymbolKind) -> String {
+        match kind {
+            SymbolKind::Const(_) => "const".into(),
+            SymbolKind::Var(_) => "var".into(),
+            SymbolKind::Param { index, .. } => format!("param {}", index),
+            SymbolKind::ForVar(_) => "for".into(),
+        }
+    }
+
+    /// The symbols of `unit` as `(name, kind)`, the uses and the diagnostic messages
+    #[allow(clippy::type_complexity)]
+    fn check(
+        items: impl IntoIterator<Item = GlobalItem>,
+    ) -> (
+        Vec<(String, String)>,
+        Vec<(String, Option<usize>)>,
+        Vec<String>,
+    ) {
+        let unit = unit(items);
+        let ids = NodeIds::new(&unit);
+        let resolution = resolve(&unit, &ids);
+        let mut uses = Uses {
+            ids: &ids,
+            resolution: &resolution,
+            uses: Vec::new(),
+        };
+        uses.visit_comp_unit(&unit);
+
+        let symbols = resolution
+            .iter()
+            .map(|(_, symbol)| (symbol.name.clone(), kind(symbol.kind)))
+            .collect();
+        let diagnostics = resolution
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect();
+        (symbols, uses.uses, diagnostics)
+    }
+
+    fn symbols(symbols: &[(&str, &str)]) -> Vec<(String, String)> {
+        symbols
+            .iter()
+            .map(|(name, kind)| (name.to_string(), kind.to_string()))
+            .collect()
+    }
+
+    fn uses(uses: &[(&str, Option<usize>)]) -> Vec<(String, Option<usize>)> {
+        uses.iter()
+            .map(|(name, symbol)| (name.to_string(), *symbol))
+            .collect()
+    }
+
+    #[test]
+    fn blocks_open_scopes() {
+        let f = func("f", u64())
+            .var("a", u64(), num(1))
+            .item(block([
+                var_decl("b", u64(), var("a")).into(),
+                assign("a", var("b")).into(),
+            ]))
+            .ret(var("b"));
+        let (defined, used, diagnostics) = check([f.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[("f", "const"), ("a", "var"), ("b", "var")])
+        );
+        assert_eq!(
+            used,
+            uses(&[("a", Some(1)), ("a", Some(1)), ("b", Some(2)), ("b", None)])
+        );
+        assert_eq!(diagnostics, ["cannot find `b` in this scope"]);
+    }
+
+    #[test]
+    fn names_defined_twice() {
+        let f = func("f", u64())
+            .var("a", u64(), num(1))
+            .var("a", u64(), num(2))
+            .item(block([var_decl("a", u64(), num(3)).into()]))
+            .ret(var("a"));
+        let g = func("f", u64()).ret(num(0));
+        let (defined, used, diagnostics) = check([f.into(), g.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[("f", "const"), ("a", "var"), ("a", "var")])
+        );
+        // The second `a` isn't defined, the one of the inner block shadows the first
+        assert_eq!(used, uses(&[("a", Some(1))]));
+        assert_eq!(
+            diagnostics,
+            [
+                "`f` is defined more than once",
+                "`a` is defined more than once"
+            ]
+        );
+
+        let unit = unit([func("f", u64())
+            .var("a", u64(), num(1))
+            .var("a", u64(), num(2))
+            .into()]);
+        let ids = NodeIds::new(&unit);
+        let resolution = resolve(&unit, &ids);
+        let labels: Vec<_> = resolution.diagnostics[0]
+            .labels
+            .iter()
+            .map(|label| (label.primary, label.message.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            [(true, "defined again here"), (false, "first defined here")]
+        );
+    }
+
+    #[test]
+    fn var_initializers_cannot_see_their_variable() {
+        let f = func("f", u64())
+            .param("a", u64())
+            .var("a", u64(), add(var("a"), num(1)))
+            .var("b", u64(), var("b"))
+            .ret(var("a"));
+        let (defined, used, diagnostics) = check([f.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[("f", "const"), ("a", "param 0"), ("a", "var"), ("b", "var")])
+        );
+        assert_eq!(used, uses(&[("a", Some(1)), ("b", None), ("a", Some(2))]));
+        assert_eq!(diagnostics, ["cannot find `b` in this scope"]);
+    }
+
+    #[test]
+    fn loop_variables_are_only_visible_in_the_loop() {
+        let f = func("f", u64())
+            .param("n", u64())
+            .item(for_(
+                "i",
+                num(0),
+                var("i"),
+                var("n"),
+                block([assign("n", var("i")).into()]),
+            ))
+            .ret(var("i"));
+        let (defined, used, diagnostics) = check([f.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[("f", "const"), ("n", "param 0"), ("i", "for")])
+        );
+        assert_eq!(
+            used,
+            uses(&[
+                ("i", None),
+                ("n", Some(1)),
+                ("n", Some(1)),
+                ("i", Some(2)),
+                ("i", None)
+            ])
+        );
+        assert_eq!(
+            diagnostics,
+            [
+                "cannot find `i` in this scope",
+                "cannot find `i` in this scope"
+            ]
+        );
+    }
+
+    #[test]
+    fn params_shadow_globals() {
+        let a = constant("a", num(1));
+        let f = func("f", u64()).param("a", u64()).ret(var("a"));
+        let g = func("g", u64()).ret(var("a"));
+        let (defined, used, diagnostics) = check([a.into(), f.into(), g.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[
+                ("a", "const"),
+                ("f", "const"),
+                ("g", "const"),
+                ("a", "param 0")
+            ])
+        );
+        assert_eq!(used, uses(&[("a", Some(3)), ("a", Some(0))]));
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn local_functions_can_be_recursive() {
+        let fact = func("fact", u64())
+            .param("n", u64())
+            .ret(mul(var("n"), call("fact", [sub(var("n"), num(1))])));
+        let main = func("main", u64())
+            .item(fact.build())
+            .ret(call("fact", [num(5)]));
+        let after = func("after", u64()).ret(call("fact", []));
+        let (defined, used, diagnostics) = check([main.into(), after.into()]);
+        assert_eq!(
+            defined,
+            symbols(&[
+                ("main", "const"),
+                ("after", "const"),
+                ("fact", "const"),
+                ("n", "param 0")
+            ])
+        );
+        assert_eq!(
+            used,
+            uses(&[
+                ("n", Some(3)),
+                ("fact", Some(2)),
+                ("n", Some(3)),
+                ("fact", Some(2)),
+                ("fact", None)
+            ])
+        );
+        assert_eq!(diagnostics, ["cannot find `fact` in this scope"]);
+    }
+}
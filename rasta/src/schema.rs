@@ -0,0 +1,79 @@
+use std::io::Read;
+
+use schemars::schema::RootSchema;
+
+use super::*;
+
+/// Generate the JSON Schema of a JSON encoded [`CompUnit`], see [`to_json`] for the shape
+pub fn json_schema() -> RootSchema {
+    schemars::schema_for!(CompUnit)
+}
+
+/// The error returned when a document is not a valid AST
+#[derive(Debug)]
+pub struct ValidationError {
+    /// The path of the first invalid node, like `global_items[0].ConstDecl.init`
+    pub path: String,
+    pub message: String,
+}
+
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid AST at {}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl<E: std::error::Error> From<serde_path_to_error::Error<E>> for ValidationError {
+    fn from(err: serde_path_to_error::Error<E>) -> Self {
+        Self {
+            path: err.path().to_string(),
+            message: err.inner().to_string(),
+        }
+    }
+}
+
+/// Check that a JSON document is a valid [`CompUnit`]
+///
+/// This is the check serde does when deserializing, with the types [`json_schema`] is
+/// generated from, not an evaluation of the schema itself.
+pub fn validate_json(json: &str) -> Result<(), ValidationError> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let _: CompUnit = serde_path_to_error::deserialize(&mut deserializer)?;
+    deserializer.end().map_err(|err| ValidationError {
+        path: ".".into(),
+        message: err.to_string(),
+    })
+}
+
+/// Check that a MessagePack document written by [`serialize`] is a valid [`CompUnit`]
+///
+/// Like [`validate_json`], this is the check serde does when deserializing. Payloads of an
+/// older schema version are upgraded with [`migrate`] first, so paths point into the
+/// upgraded payload. Structs are encoded as arrays, so their fields show up as indices in
+/// the path.
+pub fn validate_msgpack(data: &[u8]) -> Result<(), ValidationError> {
+    let container = |err: SerializeError| ValidationError {
+        path: ".".into(),
+        message: err.to_string(),
+    };
+    let (header, mut reader) = read_header(data).map_err(container)?;
+    if header.schema_version == SCHEMA_VERSION {
+        let mut deserializer = rmp_serde::Deserializer::new(&mut reader);
+        let _: CompUnit = serde_path_to_error::deserialize(&mut deserializer)?;
+    } else {
+        let payload = read_migrated(header.schema_version, &mut reader).map_err(container)?;
+        let mut deserializer = rmp_serde::Deserializer::new(payload.as_slice());
+        let _: CompUnit = serde_path_to_error::deserialize(&mut deserializer)?;
+    }
+
+    match reader.read(&mut [0]) {
+        Ok(0) => Ok(()),
+        Ok(_) => Err(ValidationError {
+            path: ".".into(),
+            message: "trailing bytes after the AST".into(),
+        }),
+        Err(err) => Err(container(SerializeError::Io(err))),
+    }
+}
@@ -0,0 +1,296 @@
+use std::io::{Chain, Cursor, Read, Write};
+
+use serde::de::DeserializeOwned;
+
+use super::*;
+
+/// The magic bytes every serialized AST starts with
+pub const MAGIC: [u8; 4] = *b"RAST";
+
+/// The version of the AST schema written by this crate
+pub const SCHEMA_VERSION: u32 = 8;
+
+/// The first byte of a version 0 AST, written before the container header existed
+const LEGACY_MARKER: u8 = 0x92;
+
+/// The error returned when an AST can't be serialized or deserialized
+#[derive(Debug)]
+pub enum SerializeError {
+    /// Reading or writing the container failed
+    Io(std::io::Error),
+    /// Encoding the AST failed, usually because the writer failed
+    Encode(rmp_serde::encode::Error),
+    /// Decoding the AST failed after reading `offset` bytes of input
+    Decode {
+        source: rmp_serde::decode::Error,
+        offset: u64,
+    },
+    /// The input doesn't start with [`MAGIC`]
+    BadMagic([u8; 4]),
+    /// The input was written with a schema version newer than this crate can read
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// Upgrading the input from schema version `from` failed
+    Migrate { from: u32, reason: String },
+    /// Encoding or decoding JSON failed
+    Json(serde_json::Error),
+    /// Encoding RON failed
+    Ron(ron::Error),
+    /// Decoding RON failed
+    RonDecode(ron::error::SpannedError),
+}
+
+impl SerializeError {
+    /// The byte offset in the input where decoding failed, if known
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Decode { offset, .. } => Some(*offset),
+            Self::BadMagic(_) => Some(0),
+            Self::UnsupportedVersion { .. } => Some(MAGIC.len() as u64),
+            Self::Io(_)
+            | Self::Encode(_)
+            | Self::Migrate { .. }
+            | Self::Json(_)
+            | Self::Ron(_)
+            | Self::RonDecode(_) => None,
+        }
+    }
+}
+
+impl core::fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to access AST container: {}", err),
+            Self::Encode(err) => write!(f, "failed to encode AST: {}", err),
+            Self::Decode { source, offset } => {
+                write!(f, "failed to decode AST at byte {}: {}", offset, source)
+            }
+            Self::BadMagic(magic) => write!(
+                f,
+                "not a rasta AST: expected magic \"{}\", found \"{}\"",
+                MAGIC.escape_ascii(),
+                magic.escape_ascii()
+            ),
+            Self::UnsupportedVersion { found, supported } => write!(
+                f,
+                "AST schema version {} is not supported, this rasta reads up to version {}",
+                found, supported
+            ),
+            Self::Migrate { from, reason } => write!(
+                f,
+                "failed to upgrade AST from schema version {}: {}",
+                from, reason
+            ),
+            Self::Json(err) => write!(f, "failed to convert AST from or to JSON: {}", err),
+            Self::Ron(err) => write!(f, "failed to encode AST as RON: {}", err),
+            Self::RonDecode(err) => write!(f, "failed to decode AST from RON: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Encode(err) => Some(err),
+            Self::Decode { source, .. } => Some(source),
+            Self::Json(err) => Some(err),
+            Self::Ron(err) => Some(err),
+            Self::RonDecode(err) => Some(err),
+            Self::BadMagic(_) | Self::UnsupportedVersion { .. } | Self::Migrate { .. } => None,
+        }
+    }
+}
+
+/// The header written in front of every serialized AST
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    /// The schema version of the payload, stored right after [`MAGIC`]. After deserializing
+    /// this is the version the input was written with, before any migration.
+    #[serde(skip)]
+    pub schema_version: u32,
+    /// The version of the rasta crate that wrote the AST
+    pub rasta_version: String,
+    /// The name of the frontend that produced the AST
+    pub producer: String,
+    /// The source files the AST was parsed from
+    pub sources: Vec<String>,
+}
+
+impl Header {
+    pub fn new(producer: String, sources: Vec<String>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            rasta_version: env!("CARGO_PKG_VERSION").into(),
+            producer,
+            sources,
+        }
+    }
+
+    /// Create a header listing every source file in the source map of `unit`
+    pub fn for_unit(producer: String, unit: &CompUnit) -> Self {
+        let sources = unit
+            .source_map
+            .files()
+            .map(|(_, file)| file.name().to_string())
+            .collect();
+        Self::new(producer, sources)
+    }
+}
+
+/// Serialize the given AST
+pub fn serialize(unit: &CompUnit) -> Result<Vec<u8>, SerializeError> {
+    let mut data = Vec::new();
+    serialize_to(unit, &mut data)?;
+    Ok(data)
+}
+
+/// Serialize the given AST into a writer
+pub fn serialize_to<W: Write>(unit: &CompUnit, writer: &mut W) -> Result<(), SerializeError> {
+    let header = Header::for_unit(env!("CARGO_PKG_NAME").into(), unit);
+    serialize_with_header_to(&header, unit, writer)
+}
+
+/// Serialize the given AST into a writer behind the given header
+///
+/// The schema version is always [`SCHEMA_VERSION`], whatever `header.schema_version` says.
+pub fn serialize_with_header_to<W: Write>(
+    header: &Header,
+    unit: &CompUnit,
+    writer: &mut W,
+) -> Result<(), SerializeError> {
+    writer.write_all(&MAGIC).map_err(SerializeError::Io)?;
+    writer
+        .write_all(&SCHEMA_VERSION.to_be_bytes())
+        .map_err(SerializeError::Io)?;
+    rmp_serde::encode::write(writer, header).map_err(SerializeError::Encode)?;
+    rmp_serde::encode::write(writer, unit).map_err(SerializeError::Encode)
+}
+
+/// Desialize the given AST
+pub fn deserialize(data: Vec<u8>) -> Result<CompUnit, SerializeError> {
+    deserialize_from(data.as_slice())
+}
+
+/// Desialize the AST from a reader
+pub fn deserialize_from<R: Read>(reader: R) -> Result<CompUnit, SerializeError> {
+    deserialize_with_header_from(reader).map(|(_, unit)| unit)
+}
+
+/// Desialize the AST and its header from a reader
+///
+/// ASTs written with an older schema version are upgraded with [`migrate`].
+pub fn deserialize_with_header_from<R: Read>(
+    reader: R,
+) -> Result<(Header, CompUnit), SerializeError> {
+    let (header, mut reader) = read_header(reader)?;
+    let unit = if header.schema_version == SCHEMA_VERSION {
+        decode(&mut reader)?
+    } else {
+        let data = read_migrated(header.schema_version, &mut reader)?;
+        rmp_serde::from_slice(&data).map_err(|err| SerializeError::Migrate {
+            from: header.schema_version,
+            reason: err.to_string(),
+        })?
+    };
+
+    Ok((header, unit))
+}
+
+/// A reader of the payload, after the header
+pub(crate) type PayloadReader<R> = CountingReader<Chain<Cursor<Vec<u8>>, R>>;
+
+/// Read the magic, the schema version and the header in front of the payload
+pub(crate) fn read_header<R: Read>(
+    mut reader: R,
+) -> Result<(Header, PayloadReader<R>), SerializeError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic).map_err(SerializeError::Io)?;
+    let legacy = magic != MAGIC && magic[0] == LEGACY_MARKER;
+    if magic != MAGIC && !legacy {
+        return Err(SerializeError::BadMagic(magic));
+    }
+
+    // A version 0 AST has no header, so the bytes we took for the magic are payload
+    let prefix = if legacy { &magic[..] } else { &[][..] };
+    let mut reader = CountingReader {
+        inner: Cursor::new(prefix.to_vec()).chain(reader),
+        count: (MAGIC.len() - prefix.len()) as u64,
+    };
+
+    let header = if legacy {
+        Header {
+            schema_version: 0,
+            rasta_version: String::new(),
+            producer: String::new(),
+            sources: Vec::new(),
+        }
+    } else {
+        let mut version = [0; 4];
+        reader.read_exact(&mut version).map_err(SerializeError::Io)?;
+        let version = u32::from_be_bytes(version);
+        if version > SCHEMA_VERSION {
+            return Err(SerializeError::UnsupportedVersion {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
+        let mut header: Header = decode(&mut reader)?;
+        header.schema_version = version;
+        header
+    };
+
+    Ok((header, reader))
+}
+
+fn decode<T: DeserializeOwned, R: Read>(
+    reader: &mut CountingReader<R>,
+) -> Result<T, SerializeError> {
+    let result = T::deserialize(&mut rmp_serde::Deserializer::new(&mut *reader));
+    result.map_err(|source| SerializeError::Decode {
+        source,
+        offset: reader.count,
+    })
+}
+
+/// Decode an older payload as a plain MessagePack value and migrate it, returning it
+/// encoded with the current schema
+pub(crate) fn read_migrated<R: Read>(
+    version: u32,
+    reader: &mut CountingReader<R>,
+) -> Result<Vec<u8>, SerializeError> {
+    let mut payload = rmpv::decode::read_value(reader).map_err(|err| SerializeError::Decode {
+        source: match err {
+            rmpv::decode::Error::InvalidMarkerRead(err) => {
+                rmp_serde::decode::Error::InvalidMarkerRead(err)
+            }
+            rmpv::decode::Error::InvalidDataRead(err) => {
+                rmp_serde::decode::Error::InvalidDataRead(err)
+            }
+            rmpv::decode::Error::DepthLimitExceeded => rmp_serde::decode::Error::DepthLimitExceeded,
+        },
+        offset: reader.count,
+    })?;
+    migrate(version, &mut payload)?;
+
+    let mut data = Vec::new();
+    rmpv::encode::write_value(&mut data, &payload).map_err(|err| SerializeError::Migrate {
+        from: version,
+        reason: err.to_string(),
+    })?;
+    Ok(data)
+}
+
+/// Keeps track of how many bytes have been read, so decode errors can report an offset
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.count += len as u64;
+        Ok(len)
+    }
+}
@@ -0,0 +1,195 @@
+use std::{ops::Range, sync::OnceLock};
+
+use super::*;
+
+/// Identifies a file in a [`SourceMap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct FileId(u32);
+
+impl FileId {
+    /// The file of compiler-generated nodes, which has no source
+    pub const DUMMY: FileId = FileId(u32::MAX);
+}
+
+/// The span of the code
+///
+/// A span is a byte range in one file of the [`SourceMap`] of its `CompUnit`. Lines and
+/// columns are looked up through the map. Spans of compiler-generated nodes are created
+/// with [`Span::dummy`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct Span {
+    file: FileId,
+    bytes: (usize, usize),
+}
+
+impl Span {
+    pub fn new(file: FileId, bytes: Range<usize>) -> Self {
+        Self {
+            file,
+            bytes: (bytes.start, bytes.end),
+        }
+    }
+
+    /// The span of a node that doesn't come from any source file
+    pub fn dummy() -> Self {
+        Self::new(FileId::DUMMY, 0..0)
+    }
+
+    pub fn is_dummy(&self) -> bool {
+        self.file == FileId::DUMMY
+    }
+
+    /// The file the span is in
+    pub fn file(&self) -> FileId {
+        self.file
+    }
+
+    /// The byte range of the span in its file
+    pub fn byte_range(&self) -> Range<usize> {
+        self.bytes.0..self.bytes.1
+    }
+
+    /// The 1-based `(line, column)` the span starts at, if the file is in `map`
+    pub fn start(&self, map: &SourceMap) -> Option<(usize, usize)> {
+        map.file(self.file)?.line_col(self.bytes.0)
+    }
+
+    /// The 1-based `(line, column)` the span ends at, exclusive
+    pub fn end(&self, map: &SourceMap) -> Option<(usize, usize)> {
+        map.file(self.file)?.line_col(self.bytes.1)
+    }
+
+    /// The source code covered by the span
+    pub fn text<'a>(&self, map: &'a SourceMap) -> Option<&'a str> {
+        map.file(self.file)?.src.get(self.byte_range())
+    }
+
+    /// The smallest span covering both `self` and `other`
+    ///
+    /// Dummy spans are ignored, both spans are expected to be in the same file.
+    pub fn merge(&self, other: &Span) -> Span {
+        if self.is_dummy() {
+            return other.clone();
+        }
+        if other.is_dummy() {
+            return self.clone();
+        }
+
+        Self {
+            file: self.file,
+            bytes: (
+                self.bytes.0.min(other.bytes.0),
+                self.bytes.1.max(other.bytes.1),
+            ),
+        }
+    }
+
+    /// The span from the start of `self` to the end of `end`
+    pub fn to(&self, end: &Span) -> Span {
+        if self.is_dummy() || end.is_dummy() {
+            return self.merge(end);
+        }
+
+        Self {
+            file: self.file,
+            bytes: (self.bytes.0, end.bytes.1),
+        }
+    }
+}
+
+/// Owns the name and contents of every source file of a `CompUnit`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file to the map, or return the id of the file with the same name
+    pub fn add_file(&mut self, name: String, src: String) -> FileId {
+        if let Some(id) = self.find_file(&name) {
+            return id;
+        }
+
+        self.files.push(SourceFile {
+            name,
+            src,
+            lines: OnceLock::new(),
+        });
+        FileId(self.files.len() as u32 - 1)
+    }
+
+    pub fn find_file(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(|id| FileId(id as u32))
+    }
+
+    pub fn file(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.0 as usize)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(id, file)| (FileId(id as u32), file))
+    }
+}
+
+/// A source file in a [`SourceMap`]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SourceFile {
+    name: String,
+    src: String,
+    /// The byte offset of every line start, computed on the first lookup
+    #[serde(skip)]
+    #[schemars(skip)]
+    lines: OnceLock<Vec<usize>>,
+}
+
+impl SourceFile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    fn line_starts(&self) -> &[usize] {
+        self.lines.get_or_init(|| {
+            std::iter::once(0)
+                .chain(self.src.match_indices('\n').map(|(i, _)| i + 1))
+                .collect()
+        })
+    }
+
+    /// The 1-based `(line, column)` of a byte offset, columns count characters
+    pub fn line_col(&self, offset: usize) -> Option<(usize, usize)> {
+        if offset > self.src.len() || !self.src.is_char_boundary(offset) {
+            return None;
+        }
+
+        let line = self.line_starts().partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts()[line];
+        Some((line + 1, self.src[start..offset].chars().count() + 1))
+    }
+
+    /// The byte offset a 1-based line starts at
+    pub fn line_offset(&self, line: usize) -> Option<usize> {
+        self.line_starts().get(line.checked_sub(1)?).copied()
+    }
+
+    /// The text of a 1-based line, without the line break
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let starts = self.line_starts();
+        let start = *starts.get(line.checked_sub(1)?)?;
+        let end = starts.get(line).map_or(self.src.len(), |&end| end);
+        Some(self.src[start..end].trim_end_matches(['\n', '\r']))
+    }
+}
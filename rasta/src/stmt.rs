@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Stmt {
     Assign(Assign),
     Return(Return),
@@ -13,7 +13,25 @@ pub enum Stmt {
     For(For),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Stmt {
+    /// The span of the statement, `None` for an empty statement
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Stmt::Assign(Assign::WriteVar(_, _, span)) => Some(span.clone()),
+            Stmt::Assign(Assign::WritePtr(_, _, span)) => Some(span.clone()),
+            Stmt::Return(ret) => Some(ret.span.clone()),
+            Stmt::Block(block) => Some(block.span.clone()),
+            Stmt::Exp(exp) => exp.as_ref().map(Exp::get_span),
+            Stmt::If(if_stmt) => Some(if_stmt.span.clone()),
+            Stmt::InlineAsm(asm) => Some(asm.span.clone()),
+            Stmt::While(while_stmt) => Some(while_stmt.span.clone()),
+            Stmt::Terminator(terminator) => Some(terminator.span()),
+            Stmt::For(for_stmt) => Some(for_stmt.span.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct For {
     pub var: String,
     pub start: Exp,
@@ -23,32 +41,32 @@ pub struct For {
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum AsmConstraint {
     In(String, Exp, Span),
     Out(String, LVal, Span),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InlineAsm {
     pub asm: String,
     pub constraints: Vec<AsmConstraint>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Assign {
     WriteVar(LVal, Exp, Span),
     WritePtr(Deref, Exp, Span),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Return {
-    pub exp: Exp,
+    pub exp: Option<Exp>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct If {
     pub cond: Exp,
     pub then: Block,
@@ -56,14 +74,14 @@ pub struct If {
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct FuncCall {
     pub ids: Vec<String>,
     pub args: Vec<Exp>,
     pub span: Span,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum BuiltinFunction {
     Import,
     Module,
@@ -71,25 +89,14 @@ pub enum BuiltinFunction {
     FirstModule,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct BuiltinFunctionCall {
     pub func: BuiltinFunction,
     pub args: Vec<Exp>,
     pub span: Span,
 }
 
-impl Deref {
-    pub fn span(&self) -> Span {
-        match self {
-            Self::DerefExp(_, span) => span.clone(),
-            Self::DerefId(_, span) => span.clone(),
-            Self::DerefPtr(_, _, span) => span.clone(),
-            Self::DerefPtrExp(_, _, span) => span.clone(),
-        }
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Terminator {
     Break(Span),
     Continue(Span),
@@ -0,0 +1,43 @@
+//! Human-readable encodings of the AST, meant for debugging and hand-written fixtures.
+//!
+//! Both JSON and RON come from the serde derives of the AST types. In JSON:
+//!
+//! * structs are objects keyed by field name, e.g. a `Number` is
+//!   `{"num": "1", "suffix": null, "radix": "Decimal", "span": {...}}`. Its `num` is
+//!   a decimal string, as it may not fit in 64 bits,
+//! * an `Option` is `null` or the bare value,
+//! * enums are externally tagged. Unit variants are just the variant name (`"Add"`),
+//!   newtype variants wrap their value (`{"Number": {...}}`) and tuple variants wrap a
+//!   list of their fields, e.g. `Exp::Binary` is `{"Binary": [lhs, "Add", rhs, span]}`
+//!   and `Deref::DerefPtr` is `{"DerefPtr": [lval, index, span]}`,
+//! * a `Span` is `{"file": id, "bytes": [start, end]}`, where `id` indexes the `files` of
+//!   the `source_map`.
+//!
+//! RON has the same structure in its own syntax: structs are
+//! `(num: "1", suffix: None, ...)`, options are `None` or `Some(...)` and enum variants
+//! are `Binary(lhs, Add, rhs, span)`.
+//!
+//! Unlike [`serialize`], these encodings have no container header and are not migrated.
+
+use super::*;
+
+/// Encode the given AST as pretty-printed JSON
+pub fn to_json(unit: &CompUnit) -> Result<String, SerializeError> {
+    serde_json::to_string_pretty(unit).map_err(SerializeError::Json)
+}
+
+/// Decode an AST from JSON
+pub fn from_json(json: &str) -> Result<CompUnit, SerializeError> {
+    serde_json::from_str(json).map_err(SerializeError::Json)
+}
+
+/// Encode the given AST as pretty-printed RON
+pub fn to_ron(unit: &CompUnit) -> Result<String, SerializeError> {
+    ron::ser::to_string_pretty(unit, ron::ser::PrettyConfig::default())
+        .map_err(SerializeError::Ron)
+}
+
+/// Decode an AST from RON
+pub fn from_ron(ron: &str) -> Result<CompUnit, SerializeError> {
+    ron::from_str(ron).map_err(SerializeError::RonDecode)
+}
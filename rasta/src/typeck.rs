@@ -0,0 +1,807 @@
+use std::collections::HashMap;
+
+use num_bigint::Sign;
+
+use super::*;
+
+/// The inferred types of a [`CompUnit`]
+#[derive(Debug, Default)]
+pub struct TypeCheck {
+    /// The type of every `Exp` whose type could be inferred
+    pub types: NodeMap<VType>,
+    /// Type mismatches and misused names
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TypeCheck {
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Infer the type of every expression of `unit` and check that statements use them right.
+///
+/// Integer literals have the type of their suffix, or take the integer type their context
+/// expects, `u64` otherwise. Float literals take the float type, `f64` otherwise. Integers
+/// never turn into floats. Strings are `i8*` and arrays are pointers to their elements.
+/// Comparisons, `!`, `&&` and `||` are `bool`, conditions `bool` or integers. Names that
+/// `resolution` couldn't resolve get no type and aren't reported again. Global constants
+/// are typed when first used, so they may use ones defined after them, but not themselves.
+pub fn type_check<'a>(
+    unit: &'a CompUnit,
+    ids: &'a NodeIds,
+    resolution: &'a Resolution,
+) -> TypeCheck {
+    let mut signatures = Signatures {
+        ids,
+        funcs: HashMap::new(),
+        classes: HashMap::new(),
+    };
+    signatures.visit_comp_unit(unit);
+
+    let mut globals = HashMap::new();
+    for item in unit.global_items.iter() {
+        if let GlobalItem::ConstDecl(decl) = item {
+            if let (ConstInitVal::Exp(exp), Some(id)) = (&decl.init, ids.const_decl(decl)) {
+                globals.insert(id, GlobalValue::Unchecked(&exp.exp));
+            }
+        }
+    }
+
+    let mut checker = Checker {
+        ids,
+        resolution,
+        funcs: signatures.funcs,
+        classes: signatures.classes,
+        values: HashMap::new(),
+        globals,
+        rets: Vec::new(),
+        check: TypeCheck::default(),
+    };
+    checker.visit_comp_unit(unit);
+    checker.check
+}
+
+/// The parameter and return types of a function or proto
+#[derive(Debug, Clone)]
+struct Signature {
+    params: Vec<VType>,
+    ret: VType,
+}
+
+/// Collects the signatures of all functions and the members of all classes up front,
+/// so they can be used before their definition
+struct Signatures<'a, 'b> {
+    ids: &'a NodeIds<'b>,
+    funcs: HashMap<NodeId, Signature>,
+    classes: HashMap<String, Vec<(String, VType)>>,
+}
+
+impl Visitor for Signatures<'_, '_> {
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        let (params, ret) = match &decl.init {
+            ConstInitVal::Function(func) => (&func.params, &func.func_type),
+            ConstInitVal::Proto(proto) => (&proto.params, &proto.func_type),
+            ConstInitVal::Class(class) => {
+                let members = class
+                    .members
+                    .iter()
+                    .map(|member| (member.id.clone(), member.ty.clone()))
+                    .collect();
+                self.classes.insert(decl.id.clone(), members);
+                return self.walk_const_decl(decl);
+            }
+            ConstInitVal::Exp(_) => return self.walk_const_decl(decl),
+        };
+        if let Some(id) = self.ids.const_decl(decl) {
+            let signature = Signature {
+                params: params.iter().map(|param| param.ty.clone()).collect(),
+                ret: ret.clone(),
+            };
+            self.funcs.insert(id, signature);
+        }
+        self.walk_const_decl(decl)
+    }
+}
+
+/// A global value constant that wasn't typed yet
+enum GlobalValue<'a> {
+    Unchecked(&'a Exp),
+    /// Its initializer is being checked, so using it again is a cycle
+    Checking,
+}
+
+struct Checker<'a, 'b> {
+    ids: &'a NodeIds<'b>,
+    resolution: &'a Resolution,
+    funcs: HashMap<NodeId, Signature>,
+    classes: HashMap<String, Vec<(String, VType)>>,
+    /// The types of variables, loop variables and value constants seen so far
+    values: HashMap<SymbolKind, VType>,
+    globals: HashMap<NodeId, GlobalValue<'a>>,
+    /// The return types of the functions being checked
+    rets: Vec<VType>,
+    check: TypeCheck,
+}
+
+fn type_name(ty: &VType) -> String {
+    let mut printer = Printer::new();
+    printer.print_vtype(ty);
+    printer.finish()
+}
+
+fn vtype(ty: VTypeEnum, star: usize, span: Span) -> VType {
+    VType { ty, star, span }
+}
+
+/// The type of comparisons
+fn bool_type(span: Span) -> VType {
+    vtype(VTypeEnum::Bool, 0, span)
+}
+
+/// Whether the literal `num` fits in the integer type `ty`, once negated if `negated`
+fn literal_fits(num: &BigInt, ty: &VTypeEnum, negated: bool) -> bool {
+    let Some(bits) = ty.bit_width() else {
+        return true;
+    };
+    // Compares the widths of the numbers, the bounds of wide types take long to compute
+    let bits = u64::from(bits);
+    if ty.is_signed() {
+        // The magnitude of the smallest value, `2^(bits - 1)`
+        let smallest = num.bits() == bits && num.trailing_zeros() == bits.checked_sub(1);
+        if negated {
+            num.sign() != Sign::Minus && (num.bits() < bits || smallest)
+        } else {
+            num.bits() < bits || (num.sign() == Sign::Minus && smallest)
+        }
+    } else if negated {
+        // Only `-0` is unsigned
+        num.bits() == 0
+    } else {
+        num.sign() != Sign::Minus && num.bits() <= bits
+    }
+}
+
+/// Whether the expression is made of literals only, so its type comes from the context
+fn is_literal(exp: &Exp) -> bool {
+    match exp {
+        Exp::Number(_) | Exp::Float(_) => true,
+        Exp::Exp(exp, _) | Exp::Unary(_, exp, _) => is_literal(exp),
+        Exp::Binary(lhs, _, rhs, _) => is_literal(lhs) && is_literal(rhs),
+        _ => false,
+    }
+}
+
+impl Checker<'_, '_> {
+    fn error(&mut self, diagnostic: Diagnostic) {
+        self.check.diagnostics.push(diagnostic);
+    }
+
+    fn mismatch(&mut self, span: Span, expected: &VType, found: &VType) {
+        self.error(Diagnostic::error("mismatched types").with_primary(
+            span,
+            format!(
+                "expected `{}`, found `{}`",
+                type_name(expected),
+                type_name(found)
+            ),
+        ));
+    }
+
+    /// Infer the type of `exp`, which must be `expected`
+    fn check_exp(&mut self, exp: &Exp, expected: &VType) {
+        if let Some(ty) = self.infer(exp, Some(expected)) {
+            if !ty.same_type(expected) {
+                self.mismatch(exp.full_span(), expected, &ty);
+            }
+        }
+    }
+
+    fn check_integer(&mut self, exp: &Exp, what: &str) -> Option<VType> {
+        let ty = self.infer(exp, None)?;
+        if !ty.is_integer() {
+            self.error(
+                Diagnostic::error(format!("{} must be an integer", what))
+                    .with_primary(exp.full_span(), format!("found `{}`", type_name(&ty))),
+            );
+            return None;
+        }
+        Some(ty)
+    }
+
+    /// Conditions are `bool`, or integers that are true when not zero
+    fn check_condition(&mut self, exp: &Exp) {
+        let Some(ty) = self.infer(exp, None) else {
+            return;
+        };
+        if !ty.is_bool() && !ty.is_integer() {
+            self.error(
+                Diagnostic::error("a condition must be a `bool` or an integer")
+                    .with_primary(exp.full_span(), format!("found `{}`", type_name(&ty))),
+            );
+        }
+    }
+
+    /// Infer the type of `exp` and remember it, `expected` only guides literals
+    fn infer(&mut self, exp: &Exp, expected: Option<&VType>) -> Option<VType> {
+        let ty = self.infer_inner(exp, expected)?;
+        self.remember(exp, &ty);
+        Some(ty)
+    }
+
+    fn remember(&mut self, exp: &Exp, ty: &VType) {
+        if let Some(id) = self.ids.exp(exp) {
+            self.check.types.insert(id, ty.clone());
+        }
+    }
+
+    /// The type of an integer literal, `negated` if it's the operand of `-`
+    fn number_type(
+        &mut self,
+        number: &Number,
+        expected: Option<&VType>,
+        negated: bool,
+    ) -> Option<VType> {
+        let ty = match (&number.suffix, expected) {
+            (Some(suffix), _) => vtype(suffix.clone(), 0, number.span.clone()),
+            (None, Some(expected)) if expected.is_integer() => expected.clone(),
+            _ => vtype(VTypeEnum::U64, 0, number.span.clone()),
+        };
+        if !ty.is_integer() {
+            self.error(
+                Diagnostic::error(format!("invalid suffix `{}`", type_name(&ty)))
+                    .with_primary(number.span.clone(), "not an integer type"),
+            );
+            return None;
+        }
+        if !literal_fits(&number.num, &ty.ty, negated) {
+            self.error(
+                Diagnostic::error(format!(
+                    "literal `{}` does not fit in `{}`",
+                    number.num,
+                    type_name(&ty)
+                ))
+                .with_primary(number.span.clone(), "out of range"),
+            );
+        }
+        Some(ty)
+    }
+
+    fn infer_inner(&mut self, exp: &Exp, expected: Option<&VType>) -> Option<VType> {
+        match exp {
+            Exp::Exp(exp, _) => self.infer(exp, expected),
+            Exp::Number(number) => self.number_type(number, expected, false),
+            Exp::Float(float) => {
+                let ty = match expected {
+                    Some(expected) if expected.is_float() => expected.clone(),
+                    _ => vtype(VTypeEnum::F64, 0, float.span.clone()),
+                };
+                let num = match ty.ty {
+                    VTypeEnum::F32 => float.num as f32 as f64,
+                    _ => float.num,
+                };
+                if num.is_infinite() {
+                    self.error(
+                        Diagnostic::error(format!(
+                            "literal `{:?}` does not fit in `{}`",
+                            float.num,
+                            type_name(&ty)
+                        ))
+                        .with_primary(float.span.clone(), "out of range"),
+                    );
+                }
+                Some(ty)
+            }
+            Exp::Str(_, span) => Some(vtype(VTypeEnum::I8, 1, span.clone())),
+            Exp::LVal(lval) => self.lval_type(lval),
+            Exp::FuncCall(call) => self.call_type(exp, call),
+            Exp::Unary(op, operand, span) => {
+                // `!` gives a `bool` whatever its operand is, only signs apply to floats
+                let (applies, ty) = match op {
+                    UnaryOp::Not => {
+                        let ty = self.infer(operand, None)?;
+                        (ty.is_integer() || ty.is_bool(), ty)
+                    }
+                    UnaryOp::BitNot => {
+                        let ty = self.infer(operand, expected)?;
+                        (ty.is_integer() || ty.is_bool(), ty)
+                    }
+                    UnaryOp::Positive | UnaryOp::Negative => match &**operand {
+                        // `-128i8` fits although `128i8` doesn't
+                        Exp::Number(number) if matches!(op, UnaryOp::Negative) => {
+                            let ty = self.number_type(number, expected, true)?;
+                            self.remember(operand, &ty);
+                            (true, ty)
+                        }
+                        _ => {
+                            let ty = self.infer(operand, expected)?;
+                            (ty.is_numeric(), ty)
+                        }
+                    },
+                };
+                if applies && matches!(op, UnaryOp::Not) {
+                    Some(bool_type(span.clone()))
+                } else if applies {
+                    Some(ty)
+                } else {
+                    self.error(
+                        Diagnostic::error(format!(
+                            "cannot apply `{}` to `{}`",
+                            unary_symbol(op),
+                            type_name(&ty)
+                        ))
+                        .with_primary(
+                            exp.full_span(),
+                            match op {
+                                UnaryOp::Not | UnaryOp::BitNot => "needs an integer or `bool`",
+                                _ => "needs a number",
+                            },
+                        ),
+                    );
+                    None
+                }
+            }
+            Exp::Binary(lhs, op, rhs, span) => self.binary_type(exp, lhs, op, rhs, span, expected),
+            Exp::GetAddr(get_addr) => {
+                let mut ty = self.lval_type(&get_addr.lval)?;
+                ty.star += 1;
+                Some(ty)
+            }
+            Exp::Deref(deref) => self.deref_type(deref),
+            Exp::Array(array) => {
+                let elem = expected
+                    .filter(|expected| expected.is_pointer())
+                    .map(|expected| {
+                        vtype(
+                            expected.ty.clone(),
+                            expected.star - 1,
+                            expected.span.clone(),
+                        )
+                    });
+                let mut ty = match array.as_ref() {
+                    Array::List(values, span) => {
+                        let mut values = values.iter();
+                        let Some(first) = values.next() else {
+                            return expected.cloned().or(Some(vtype(
+                                VTypeEnum::Void,
+                                1,
+                                span.clone(),
+                            )));
+                        };
+                        let elem = self.infer(first, elem.as_ref())?;
+                        for value in values {
+                            self.check_exp(value, &elem);
+                        }
+                        elem
+                    }
+                    Array::Template(value, count, _) => {
+                        self.check_integer(&count.exp, "an array length");
+                        self.infer(value, elem.as_ref())?
+                    }
+                };
+                ty.star += 1;
+                Some(ty)
+            }
+        }
+    }
+
+    fn binary_type(
+        &mut self,
+        exp: &Exp,
+        lhs: &Exp,
+        op: &BinaryOp,
+        rhs: &Exp,
+        span: &Span,
+        expected: Option<&VType>,
+    ) -> Option<VType> {
+        // The operands of `&&` and `||` are conditions, the shift amount is any integer
+        if op.is_short_circuit() {
+            self.check_condition(lhs);
+            self.check_condition(rhs);
+            return Some(bool_type(span.clone()));
+        }
+        if matches!(op, BinaryOp::Shl | BinaryOp::Shr) {
+            let ty = self.infer(lhs, expected)?;
+            self.check_integer(rhs, "a shift amount")?;
+            if !ty.is_integer() {
+                self.error(
+                    Diagnostic::error(format!(
+                        "cannot use `{}` on `{}`",
+                        binary_symbol(op),
+                        type_name(&ty)
+                    ))
+                    .with_primary(exp.full_span(), "needs an integer"),
+                );
+                return None;
+            }
+            return Some(ty);
+        }
+
+        let comparison = op.is_comparison();
+        let expected = if comparison { None } else { expected };
+
+        // A literal operand takes the type of the other one
+        let (first, second) = if is_literal(lhs) {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+        let first_ty = self.infer(first, expected)?;
+
+        let offset = matches!(op, BinaryOp::Add | BinaryOp::Sub);
+        if first_ty.is_pointer() && offset && std::ptr::eq(first, lhs) {
+            self.check_integer(second, "a pointer offset")?;
+            return Some(first_ty);
+        }
+
+        let second_ty = self.infer(second, Some(&first_ty))?;
+        if !second_ty.same_type(&first_ty) {
+            let (lhs_ty, rhs_ty) = if std::ptr::eq(first, lhs) {
+                (&first_ty, &second_ty)
+            } else {
+                (&second_ty, &first_ty)
+            };
+            self.error(
+                Diagnostic::error(format!(
+                    "cannot use `{}` on `{}` and `{}`",
+                    binary_symbol(op),
+                    type_name(lhs_ty),
+                    type_name(rhs_ty)
+                ))
+                .with_primary(exp.full_span(), "operands of different types"),
+            );
+            return None;
+        }
+
+        if comparison {
+            if first_ty.is_void() {
+                self.error(
+                    Diagnostic::error("cannot compare `void` values")
+                        .with_primary(exp.full_span(), ""),
+                );
+                return None;
+            }
+            return Some(bool_type(span.clone()));
+        }
+        let bitwise = matches!(op, BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor);
+        let applies = if bitwise {
+            first_ty.is_integer() || first_ty.is_bool()
+        } else {
+            first_ty.is_numeric()
+        };
+        if !applies {
+            self.error(
+                Diagnostic::error(format!(
+                    "cannot use `{}` on `{}`",
+                    binary_symbol(op),
+                    type_name(&first_ty)
+                ))
+                .with_primary(
+                    exp.full_span(),
+                    if bitwise {
+                        "needs integers or `bool`s"
+                    } else {
+                        "needs numbers"
+                    },
+                ),
+            );
+            return None;
+        }
+        Some(first_ty)
+    }
+
+    fn call_type(&mut self, exp: &Exp, call: &FuncCall) -> Option<VType> {
+        let symbol = self.ids.exp(exp).and_then(|id| self.resolution.lookup(id));
+        let signature = match symbol.map(|symbol| symbol.kind) {
+            Some(SymbolKind::Const(id)) if self.funcs.contains_key(&id) => {
+                Some(self.funcs[&id].clone())
+            }
+            Some(_) => {
+                self.error(
+                    Diagnostic::error(format!("`{}` is not a function", call.ids.join(".")))
+                        .with_primary(call.span.clone(), "called here")
+                        .with_secondary(symbol.unwrap().span.clone(), "defined here"),
+                );
+                None
+            }
+            None => None,
+        };
+
+        let Some(signature) = signature else {
+            for arg in call.args.iter() {
+                self.infer(arg, None);
+            }
+            return None;
+        };
+
+        if signature.params.len() != call.args.len() {
+            self.error(
+                Diagnostic::error(format!(
+                    "`{}` takes {} argument{} but {} {} supplied",
+                    call.ids.join("."),
+                    signature.params.len(),
+                    if signature.params.len() == 1 { "" } else { "s" },
+                    call.args.len(),
+                    if call.args.len() == 1 { "was" } else { "were" },
+                ))
+                .with_primary(call.span.clone(), "wrong number of arguments"),
+            );
+        }
+        for (i, arg) in call.args.iter().enumerate() {
+            match signature.params.get(i) {
+                Some(param) => self.check_exp(arg, param),
+                None => {
+                    self.infer(arg, None);
+                }
+            }
+        }
+
+        Some(signature.ret)
+    }
+
+    /// The type of a symbol used as a value
+    fn symbol_type(&mut self, symbol: &Symbol, span: &Span) -> Option<VType> {
+        match symbol.kind {
+            SymbolKind::Param { func, index } => Some(self.funcs.get(&func)?.params[index].clone()),
+            SymbolKind::Const(id) if self.funcs.contains_key(&id) => {
+                self.error(
+                    Diagnostic::error(format!("function `{}` used as a value", symbol.name))
+                        .with_primary(span.clone(), "not called"),
+                );
+                None
+            }
+            SymbolKind::Const(id) if self.globals.contains_key(&id) => {
+                self.global_type(id, Some((symbol, span)))
+            }
+            kind => self.values.get(&kind).cloned(),
+        }
+    }
+
+    /// Type the global constant `id` if it wasn't yet, `used` where it's used from
+    fn global_type(&mut self, id: NodeId, used: Option<(&Symbol, &Span)>) -> Option<VType> {
+        let kind = SymbolKind::Const(id);
+        match self.globals.insert(id, GlobalValue::Checking) {
+            Some(GlobalValue::Unchecked(exp)) => {
+                let ty = self.infer(exp, None);
+                self.globals.remove(&id);
+                if let Some(ty) = &ty {
+                    self.values.insert(kind, ty.clone());
+                }
+                ty
+            }
+            Some(GlobalValue::Checking) => {
+                if let Some((symbol, span)) = used {
+                    self.error(
+                        Diagnostic::error(format!("constant `{}` depends on itself", symbol.name))
+                            .with_primary(span.clone(), "used in its own definition")
+                            .with_secondary(symbol.span.clone(), "defined here"),
+                    );
+                }
+                None
+            }
+            None => {
+                self.globals.remove(&id);
+                self.values.get(&kind).cloned()
+            }
+        }
+    }
+
+    fn lval_type(&mut self, lval: &LVal) -> Option<VType> {
+        let id = self.ids.lval(lval)?;
+        let symbol = self.resolution.lookup(id)?;
+        let mut ty = self.symbol_type(symbol, &lval.span)?;
+
+        for member in lval.ids.iter().skip(1) {
+            let found = match &ty.ty {
+                VTypeEnum::Others(class) if !ty.is_pointer() => self
+                    .classes
+                    .get(class)
+                    .and_then(|members| members.iter().find(|(id, _)| id == member))
+                    .map(|(_, ty)| ty.clone()),
+                _ => None,
+            };
+            let Some(found) = found else {
+                self.error(
+                    Diagnostic::error(format!("no member `{}` on `{}`", member, type_name(&ty)))
+                        .with_primary(lval.span.clone(), "unknown member"),
+                );
+                return None;
+            };
+            ty = found;
+        }
+
+        match &lval.exp {
+            Some(index) => self.index_type(ty, index, &lval.span),
+            None => Some(ty),
+        }
+    }
+
+    fn pointee(&mut self, mut ty: VType, span: &Span) -> Option<VType> {
+        if !ty.is_pointer() {
+            self.error(
+                Diagnostic::error(format!("cannot dereference `{}`", type_name(&ty)))
+                    .with_primary(span.clone(), "not a pointer"),
+            );
+            return None;
+        }
+        ty.star -= 1;
+        Some(ty)
+    }
+
+    fn index_type(&mut self, ty: VType, index: &Exp, span: &Span) -> Option<VType> {
+        self.check_integer(index, "an index");
+        self.pointee(ty, span)
+    }
+
+    fn deref_type(&mut self, deref: &Deref) -> Option<VType> {
+        match deref {
+            Deref::DerefId(lval, span) => {
+                let ty = self.lval_type(lval)?;
+                self.pointee(ty, span)
+            }
+            Deref::DerefExp(exp, span) => {
+                let ty = self.infer(exp, None)?;
+                self.pointee(ty, span)
+            }
+            Deref::DerefPtr(lval, index, span) => {
+                let ty = self.lval_type(lval)?;
+                self.index_type(ty, index, span)
+            }
+            Deref::DerefPtrExp(exp, index, span) => {
+                let ty = self.infer(exp, None)?;
+                self.index_type(ty, index, span)
+            }
+        }
+    }
+}
+
+impl Visitor for Checker<'_, '_> {
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        match (&decl.init, self.ids.const_decl(decl)) {
+            (ConstInitVal::Exp(_), Some(id)) if self.globals.contains_key(&id) => {
+                self.global_type(id, None);
+            }
+            (ConstInitVal::Exp(exp), id) => {
+                let ty = self.infer(&exp.exp, None);
+                if let (Some(ty), Some(id)) = (ty, id) {
+                    self.values.insert(SymbolKind::Const(id), ty);
+                }
+            }
+            _ => self.walk_const_decl(decl),
+        }
+    }
+
+    fn visit_func_def(&mut self, func: &FuncDef) {
+        self.rets.push(func.func_type.clone());
+        self.visit_block(&func.block);
+        self.rets.pop();
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Const(decl) => self.visit_const_decl(decl),
+            Decl::Var(var) => {
+                self.check_exp(&var.init.exp, &var.ty);
+                if let Some(id) = self.ids.decl(decl) {
+                    self.values.insert(SymbolKind::Var(id), var.ty.clone());
+                }
+            }
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        let Stmt::For(for_stmt) = stmt else {
+            return self.walk_stmt(stmt);
+        };
+        // Literal bounds take the type of the first bound that isn't one
+        let bounds = [&for_stmt.start, &for_stmt.end, &for_stmt.step];
+        let first = bounds
+            .iter()
+            .position(|bound| !is_literal(bound))
+            .unwrap_or(0);
+        let others = bounds
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != first)
+            .map(|(_, bound)| bound);
+        if let Some(ty) = self.check_integer(bounds[first], "a loop bound") {
+            for bound in others {
+                self.check_exp(bound, &ty);
+            }
+            if let Some(id) = self.ids.stmt(stmt) {
+                self.values.insert(SymbolKind::ForVar(id), ty);
+            }
+        } else {
+            for bound in others {
+                self.infer(bound, None);
+            }
+        }
+        self.visit_block(&for_stmt.then);
+    }
+
+    fn visit_assign(&mut self, assign: &Assign) {
+        let (target, exp) = match assign {
+            Assign::WriteVar(lval, exp, _) => {
+                let symbol = self
+                    .ids
+                    .lval(lval)
+                    .and_then(|id| self.resolution.lookup(id));
+                if let Some(
+                    symbol @ Symbol {
+                        kind: SymbolKind::Const(_),
+                        ..
+                    },
+                ) = symbol
+                {
+                    self.error(
+                        Diagnostic::error(format!("cannot assign to constant `{}`", symbol.name))
+                            .with_primary(lval.span.clone(), "assigned here")
+                            .with_secondary(symbol.span.clone(), "defined here"),
+                    );
+                }
+                (self.lval_type(lval), exp)
+            }
+            Assign::WritePtr(deref, exp, _) => (self.deref_type(deref), exp),
+        };
+        match target {
+            Some(target) => self.check_exp(exp, &target),
+            None => {
+                self.infer(exp, None);
+            }
+        }
+    }
+
+    fn visit_return(&mut self, ret: &Return) {
+        let Some(expected) = self.rets.last().cloned() else {
+            return;
+        };
+        match &ret.exp {
+            Some(exp) if expected.is_void() => {
+                self.infer(exp, None);
+                self.error(
+                    Diagnostic::error("a `void` function cannot return a value")
+                        .with_primary(exp.full_span(), "returned here"),
+                );
+            }
+            Some(exp) => self.check_exp(exp, &expected),
+            None if !expected.is_void() => self.error(
+                Diagnostic::error(format!(
+                    "expected a `{}` return value",
+                    type_name(&expected)
+                ))
+                .with_primary(ret.span.clone(), "returns nothing"),
+            ),
+            None => {}
+        }
+    }
+
+    fn visit_if(&mut self, if_stmt: &If) {
+        self.check_condition(&if_stmt.cond);
+        self.visit_block(&if_stmt.then);
+        if let Some(else_then) = &if_stmt.else_then {
+            self.visit_block(else_then);
+        }
+    }
+
+    fn visit_while(&mut self, while_stmt: &While) {
+        self.check_condition(&while_stmt.cond);
+        self.visit_block(&while_stmt.then);
+    }
+
+    fn visit_inline_asm(&mut self, asm: &InlineAsm) {
+        for constraint in asm.constraints.iter() {
+            match constraint {
+                AsmConstraint::In(_, exp, _) => {
+                    self.infer(exp, None);
+                }
+                AsmConstraint::Out(_, lval, _) => {
+                    self.lval_type(lval);
+                }
+            }
+        }
+    }
+
+    fn visit_exp(&mut self, exp: &Exp) {
+        self.infer(exp, None);
+    }
+}
@@ -1,16 +1,113 @@
 use super::*;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum VTypeEnum {
     U64,
     I8,
     Void,
     Others(String),
+    U8,
+    U16,
+    U32,
+    U128,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+    /// An unsigned integer of any width
+    UInt(u32),
+    /// A signed integer of any width
+    SInt(u32),
+    F32,
+    F64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl VTypeEnum {
+    /// The number of bits of an integer or `bool`
+    pub fn bit_width(&self) -> Option<u32> {
+        match self {
+            Self::Bool => Some(1),
+            Self::U8 | Self::I8 => Some(8),
+            Self::U16 | Self::I16 => Some(16),
+            Self::U32 | Self::I32 => Some(32),
+            Self::U64 | Self::I64 => Some(64),
+            Self::U128 | Self::I128 => Some(128),
+            Self::UInt(bits) | Self::SInt(bits) => Some(*bits),
+            Self::Void | Self::Others(_) | Self::F32 | Self::F64 => None,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128 | Self::SInt(_)
+        )
+    }
+
+    /// The integer type with the given signedness and width, `UInt(8)` is `U8`
+    pub fn int(signed: bool, bits: u32) -> Self {
+        match (signed, bits) {
+            (false, 8) => Self::U8,
+            (false, 16) => Self::U16,
+            (false, 32) => Self::U32,
+            (false, 64) => Self::U64,
+            (false, 128) => Self::U128,
+            (true, 8) => Self::I8,
+            (true, 16) => Self::I16,
+            (true, 32) => Self::I32,
+            (true, 64) => Self::I64,
+            (true, 128) => Self::I128,
+            (false, bits) => Self::UInt(bits),
+            (true, bits) => Self::SInt(bits),
+        }
+    }
+
+    /// The same type, with `UInt`/`SInt` of a standard width replaced by its name
+    pub fn normalized(&self) -> Self {
+        match self {
+            Self::UInt(bits) => Self::int(false, *bits),
+            Self::SInt(bits) => Self::int(true, *bits),
+            ty => ty.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VType {
     pub ty: VTypeEnum,
     pub star: usize,
     pub span: Span,
 }
+
+impl VType {
+    /// Whether both are the same type, wherever they are written
+    pub fn same_type(&self, other: &VType) -> bool {
+        self.ty.normalized() == other.ty.normalized() && self.star == other.star
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        self.star != 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        !self.is_pointer() && self.ty != VTypeEnum::Bool && self.ty.bit_width().is_some()
+    }
+
+    pub fn is_float(&self) -> bool {
+        !self.is_pointer() && matches!(self.ty, VTypeEnum::F32 | VTypeEnum::F64)
+    }
+
+    /// Whether arithmetic works on the type
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    pub fn is_bool(&self) -> bool {
+        !self.is_pointer() && self.ty == VTypeEnum::Bool
+    }
+
+    pub fn is_void(&self) -> bool {
+        !self.is_pointer() && self.ty == VTypeEnum::Void
+    }
+}
@@ -0,0 +1,476 @@
+use super::*;
+
+/// Walks the AST by shared reference.
+///
+/// Every `visit_*` method calls the matching `walk_*` method by default, and every
+/// `walk_*` method visits the children of the node. Override `visit_*` to handle a node
+/// and call `walk_*` from it if the children should still be visited.
+pub trait Visitor {
+    fn visit_comp_unit(&mut self, unit: &CompUnit) {
+        self.walk_comp_unit(unit)
+    }
+
+    fn walk_comp_unit(&mut self, unit: &CompUnit) {
+        for item in unit.global_items.iter() {
+            self.visit_global_item(item);
+        }
+        self.visit_span(&unit.span);
+    }
+
+    fn visit_global_item(&mut self, item: &GlobalItem) {
+        self.walk_global_item(item)
+    }
+
+    fn walk_global_item(&mut self, item: &GlobalItem) {
+        match item {
+            GlobalItem::ConstDecl(decl) => self.visit_const_decl(decl),
+            GlobalItem::BuiltinFnCall(call) => self.visit_builtin_function_call(call),
+            GlobalItem::InlineAsm(asm) => self.visit_inline_asm(asm),
+        }
+    }
+
+    fn visit_attributes(&mut self, attr: &Attributes) {
+        self.walk_attributes(attr)
+    }
+
+    fn walk_attributes(&mut self, attr: &Attributes) {
+        self.visit_span(&attr.span);
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        self.walk_const_decl(decl)
+    }
+
+    fn walk_const_decl(&mut self, decl: &ConstDecl) {
+        if let Some(attr) = &decl.attr {
+            self.visit_attributes(attr);
+        }
+        self.visit_const_init_val(&decl.init);
+        self.visit_span(&decl.span);
+    }
+
+    fn visit_const_init_val(&mut self, init: &ConstInitVal) {
+        self.walk_const_init_val(init)
+    }
+
+    fn walk_const_init_val(&mut self, init: &ConstInitVal) {
+        match init {
+            ConstInitVal::Exp(exp) => self.visit_const_exp(exp),
+            ConstInitVal::Function(func) => self.visit_func_def(func),
+            ConstInitVal::Proto(proto) => self.visit_proto_def(proto),
+            ConstInitVal::Class(class) => self.visit_class_def(class),
+        }
+    }
+
+    fn visit_const_exp(&mut self, exp: &ConstExp) {
+        self.walk_const_exp(exp)
+    }
+
+    fn walk_const_exp(&mut self, exp: &ConstExp) {
+        self.visit_exp(&exp.exp);
+    }
+
+    fn visit_func_def(&mut self, func: &FuncDef) {
+        self.walk_func_def(func)
+    }
+
+    fn walk_func_def(&mut self, func: &FuncDef) {
+        for param in func.params.iter() {
+            self.visit_param(param);
+        }
+        self.visit_vtype(&func.func_type);
+        self.visit_block(&func.block);
+        self.visit_span(&func.span);
+    }
+
+    fn visit_proto_def(&mut self, proto: &ProtoDef) {
+        self.walk_proto_def(proto)
+    }
+
+    fn walk_proto_def(&mut self, proto: &ProtoDef) {
+        for param in proto.params.iter() {
+            self.visit_param(param);
+        }
+        self.visit_vtype(&proto.func_type);
+        self.visit_span(&proto.span);
+    }
+
+    fn visit_param(&mut self, param: &Param) {
+        self.walk_param(param)
+    }
+
+    fn walk_param(&mut self, param: &Param) {
+        self.visit_vtype(&param.ty);
+        self.visit_span(&param.span);
+    }
+
+    fn visit_class_def(&mut self, class: &ClassDef) {
+        self.walk_class_def(class)
+    }
+
+    fn walk_class_def(&mut self, class: &ClassDef) {
+        for member in class.members.iter() {
+            self.visit_class_member(member);
+        }
+        for decl in class.consts.iter() {
+            self.visit_const_decl(decl);
+        }
+        self.visit_span(&class.span);
+    }
+
+    fn visit_class_member(&mut self, member: &ClassMember) {
+        self.walk_class_member(member)
+    }
+
+    fn walk_class_member(&mut self, member: &ClassMember) {
+        self.visit_vtype(&member.ty);
+        self.visit_span(&member.span);
+    }
+
+    fn visit_new_class(&mut self, class: &NewClass) {
+        self.walk_new_class(class)
+    }
+
+    fn walk_new_class(&mut self, class: &NewClass) {
+        for member in class.members.iter() {
+            self.visit_new_class_member(member);
+        }
+        self.visit_span(&class.span);
+    }
+
+    fn visit_new_class_member(&mut self, member: &NewClassMember) {
+        self.walk_new_class_member(member)
+    }
+
+    fn walk_new_class_member(&mut self, member: &NewClassMember) {
+        self.visit_exp(&member.val);
+        self.visit_span(&member.span);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.walk_block(block)
+    }
+
+    fn walk_block(&mut self, block: &Block) {
+        for item in block.items.iter() {
+            self.visit_block_item(item);
+        }
+        self.visit_span(&block.span);
+    }
+
+    fn visit_block_item(&mut self, item: &BlockItem) {
+        self.walk_block_item(item)
+    }
+
+    fn walk_block_item(&mut self, item: &BlockItem) {
+        match item {
+            BlockItem::Decl(decl) => self.visit_decl(decl),
+            BlockItem::Stmt(stmt) => self.visit_stmt(stmt),
+        }
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        self.walk_decl(decl)
+    }
+
+    fn walk_decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Const(decl) => self.visit_const_decl(decl),
+            Decl::Var(decl) => self.visit_var_decl(decl),
+        }
+    }
+
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        self.walk_var_decl(decl)
+    }
+
+    fn walk_var_decl(&mut self, decl: &VarDecl) {
+        self.visit_vtype(&decl.ty);
+        self.visit_init_val(&decl.init);
+        self.visit_span(&decl.span);
+    }
+
+    fn visit_init_val(&mut self, init: &InitVal) {
+        self.walk_init_val(init)
+    }
+
+    fn walk_init_val(&mut self, init: &InitVal) {
+        self.visit_exp(&init.exp);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.walk_stmt(stmt)
+    }
+
+    fn walk_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assign(assign) => self.visit_assign(assign),
+            Stmt::Return(ret) => self.visit_return(ret),
+            Stmt::Block(block) => self.visit_block(block),
+            Stmt::Exp(exp) => {
+                if let Some(exp) = exp {
+                    self.visit_exp(exp);
+                }
+            }
+            Stmt::If(if_stmt) => self.visit_if(if_stmt),
+            Stmt::InlineAsm(asm) => self.visit_inline_asm(asm),
+            Stmt::While(while_stmt) => self.visit_while(while_stmt),
+            Stmt::Terminator(terminator) => self.visit_terminator(terminator),
+            Stmt::For(for_stmt) => self.visit_for(for_stmt),
+        }
+    }
+
+    fn visit_assign(&mut self, assign: &Assign) {
+        self.walk_assign(assign)
+    }
+
+    fn walk_assign(&mut self, assign: &Assign) {
+        match assign {
+            Assign::WriteVar(lval, exp, span) => {
+                self.visit_lval(lval);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Assign::WritePtr(deref, exp, span) => {
+                self.visit_deref(deref);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_return(&mut self, ret: &Return) {
+        self.walk_return(ret)
+    }
+
+    fn walk_return(&mut self, ret: &Return) {
+        if let Some(exp) = &ret.exp {
+            self.visit_exp(exp);
+        }
+        self.visit_span(&ret.span);
+    }
+
+    fn visit_if(&mut self, if_stmt: &If) {
+        self.walk_if(if_stmt)
+    }
+
+    fn walk_if(&mut self, if_stmt: &If) {
+        self.visit_exp(&if_stmt.cond);
+        self.visit_block(&if_stmt.then);
+        if let Some(else_then) = &if_stmt.else_then {
+            self.visit_block(else_then);
+        }
+        self.visit_span(&if_stmt.span);
+    }
+
+    fn visit_while(&mut self, while_stmt: &While) {
+        self.walk_while(while_stmt)
+    }
+
+    fn walk_while(&mut self, while_stmt: &While) {
+        self.visit_exp(&while_stmt.cond);
+        self.visit_block(&while_stmt.then);
+        self.visit_span(&while_stmt.span);
+    }
+
+    fn visit_for(&mut self, for_stmt: &For) {
+        self.walk_for(for_stmt)
+    }
+
+    fn walk_for(&mut self, for_stmt: &For) {
+        self.visit_exp(&for_stmt.start);
+        self.visit_exp(&for_stmt.end);
+        self.visit_exp(&for_stmt.step);
+        self.visit_block(&for_stmt.then);
+        self.visit_span(&for_stmt.span);
+    }
+
+    fn visit_inline_asm(&mut self, asm: &InlineAsm) {
+        self.walk_inline_asm(asm)
+    }
+
+    fn walk_inline_asm(&mut self, asm: &InlineAsm) {
+        for constraint in asm.constraints.iter() {
+            self.visit_asm_constraint(constraint);
+        }
+        self.visit_span(&asm.span);
+    }
+
+    fn visit_asm_constraint(&mut self, constraint: &AsmConstraint) {
+        self.walk_asm_constraint(constraint)
+    }
+
+    fn walk_asm_constraint(&mut self, constraint: &AsmConstraint) {
+        match constraint {
+            AsmConstraint::In(_, exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            AsmConstraint::Out(_, lval, span) => {
+                self.visit_lval(lval);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator) {
+        self.walk_terminator(terminator)
+    }
+
+    fn walk_terminator(&mut self, terminator: &Terminator) {
+        match terminator {
+            Terminator::Break(span) | Terminator::Continue(span) => self.visit_span(span),
+        }
+    }
+
+    fn visit_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        self.walk_builtin_function_call(call)
+    }
+
+    fn walk_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        for arg in call.args.iter() {
+            self.visit_exp(arg);
+        }
+        self.visit_span(&call.span);
+    }
+
+    fn visit_exp(&mut self, exp: &Exp) {
+        self.walk_exp(exp)
+    }
+
+    fn walk_exp(&mut self, exp: &Exp) {
+        match exp {
+            Exp::Exp(exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Exp::Number(number) => self.visit_number(number),
+            Exp::LVal(lval) => self.visit_lval(lval),
+            Exp::FuncCall(call) => self.visit_func_call(call),
+            Exp::Str(_, span) => self.visit_span(span),
+            Exp::Unary(op, exp, span) => {
+                self.visit_unary_op(op);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Exp::Binary(lhs, op, rhs, span) => {
+                self.visit_exp(lhs);
+                self.visit_binary_op(op);
+                self.visit_exp(rhs);
+                self.visit_span(span);
+            }
+            Exp::GetAddr(get_addr) => self.visit_get_addr(get_addr),
+            Exp::Deref(deref) => self.visit_deref(deref),
+            Exp::Array(array) => self.visit_array(array),
+            Exp::Float(float) => self.visit_float(float),
+        }
+    }
+
+    fn visit_number(&mut self, number: &Number) {
+        self.walk_number(number)
+    }
+
+    fn walk_number(&mut self, number: &Number) {
+        self.visit_span(&number.span);
+    }
+
+    fn visit_float(&mut self, float: &Float) {
+        self.walk_float(float)
+    }
+
+    fn walk_float(&mut self, float: &Float) {
+        self.visit_span(&float.span);
+    }
+
+    fn visit_lval(&mut self, lval: &LVal) {
+        self.walk_lval(lval)
+    }
+
+    fn walk_lval(&mut self, lval: &LVal) {
+        if let Some(exp) = &lval.exp {
+            self.visit_exp(exp);
+        }
+        self.visit_span(&lval.span);
+    }
+
+    fn visit_func_call(&mut self, call: &FuncCall) {
+        self.walk_func_call(call)
+    }
+
+    fn walk_func_call(&mut self, call: &FuncCall) {
+        for arg in call.args.iter() {
+            self.visit_exp(arg);
+        }
+        self.visit_span(&call.span);
+    }
+
+    fn visit_get_addr(&mut self, get_addr: &GetAddr) {
+        self.walk_get_addr(get_addr)
+    }
+
+    fn walk_get_addr(&mut self, get_addr: &GetAddr) {
+        self.visit_lval(&get_addr.lval);
+        self.visit_span(&get_addr.span);
+    }
+
+    fn visit_deref(&mut self, deref: &Deref) {
+        self.walk_deref(deref)
+    }
+
+    fn walk_deref(&mut self, deref: &Deref) {
+        match deref {
+            Deref::DerefId(lval, span) => {
+                self.visit_lval(lval);
+                self.visit_span(span);
+            }
+            Deref::DerefExp(exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Deref::DerefPtrExp(exp, index, span) => {
+                self.visit_exp(exp);
+                self.visit_exp(index);
+                self.visit_span(span);
+            }
+            Deref::DerefPtr(lval, index, span) => {
+                self.visit_lval(lval);
+                self.visit_exp(index);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_array(&mut self, array: &Array) {
+        self.walk_array(array)
+    }
+
+    fn walk_array(&mut self, array: &Array) {
+        match array {
+            Array::List(values, span) => {
+                for value in values.iter() {
+                    self.visit_exp(value);
+                }
+                self.visit_span(span);
+            }
+            Array::Template(value, count, span) => {
+                self.visit_exp(value);
+                self.visit_const_exp(count);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_vtype(&mut self, vtype: &VType) {
+        self.walk_vtype(vtype)
+    }
+
+    fn walk_vtype(&mut self, vtype: &VType) {
+        self.visit_span(&vtype.span);
+    }
+
+    fn visit_unary_op(&mut self, _op: &UnaryOp) {}
+
+    fn visit_binary_op(&mut self, _op: &BinaryOp) {}
+
+    fn visit_span(&mut self, _span: &Span) {}
+}
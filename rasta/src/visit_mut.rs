@@ -0,0 +1,472 @@
+use super::*;
+
+/// Walks the AST by mutable reference, see [`Visitor`] for how the methods fit together.
+pub trait VisitorMut {
+    fn visit_comp_unit(&mut self, unit: &mut CompUnit) {
+        self.walk_comp_unit(unit)
+    }
+
+    fn walk_comp_unit(&mut self, unit: &mut CompUnit) {
+        for item in unit.global_items.iter_mut() {
+            self.visit_global_item(item);
+        }
+        self.visit_span(&mut unit.span);
+    }
+
+    fn visit_global_item(&mut self, item: &mut GlobalItem) {
+        self.walk_global_item(item)
+    }
+
+    fn walk_global_item(&mut self, item: &mut GlobalItem) {
+        match item {
+            GlobalItem::ConstDecl(decl) => self.visit_const_decl(decl),
+            GlobalItem::BuiltinFnCall(call) => self.visit_builtin_function_call(call),
+            GlobalItem::InlineAsm(asm) => self.visit_inline_asm(asm),
+        }
+    }
+
+    fn visit_attributes(&mut self, attr: &mut Attributes) {
+        self.walk_attributes(attr)
+    }
+
+    fn walk_attributes(&mut self, attr: &mut Attributes) {
+        self.visit_span(&mut attr.span);
+    }
+
+    fn visit_const_decl(&mut self, decl: &mut ConstDecl) {
+        self.walk_const_decl(decl)
+    }
+
+    fn walk_const_decl(&mut self, decl: &mut ConstDecl) {
+        if let Some(attr) = &mut decl.attr {
+            self.visit_attributes(attr);
+        }
+        self.visit_const_init_val(&mut decl.init);
+        self.visit_span(&mut decl.span);
+    }
+
+    fn visit_const_init_val(&mut self, init: &mut ConstInitVal) {
+        self.walk_const_init_val(init)
+    }
+
+    fn walk_const_init_val(&mut self, init: &mut ConstInitVal) {
+        match init {
+            ConstInitVal::Exp(exp) => self.visit_const_exp(exp),
+            ConstInitVal::Function(func) => self.visit_func_def(func),
+            ConstInitVal::Proto(proto) => self.visit_proto_def(proto),
+            ConstInitVal::Class(class) => self.visit_class_def(class),
+        }
+    }
+
+    fn visit_const_exp(&mut self, exp: &mut ConstExp) {
+        self.walk_const_exp(exp)
+    }
+
+    fn walk_const_exp(&mut self, exp: &mut ConstExp) {
+        self.visit_exp(&mut exp.exp);
+    }
+
+    fn visit_func_def(&mut self, func: &mut FuncDef) {
+        self.walk_func_def(func)
+    }
+
+    fn walk_func_def(&mut self, func: &mut FuncDef) {
+        for param in func.params.iter_mut() {
+            self.visit_param(param);
+        }
+        self.visit_vtype(&mut func.func_type);
+        self.visit_block(&mut func.block);
+        self.visit_span(&mut func.span);
+    }
+
+    fn visit_proto_def(&mut self, proto: &mut ProtoDef) {
+        self.walk_proto_def(proto)
+    }
+
+    fn walk_proto_def(&mut self, proto: &mut ProtoDef) {
+        for param in proto.params.iter_mut() {
+            self.visit_param(param);
+        }
+        self.visit_vtype(&mut proto.func_type);
+        self.visit_span(&mut proto.span);
+    }
+
+    fn visit_param(&mut self, param: &mut Param) {
+        self.walk_param(param)
+    }
+
+    fn walk_param(&mut self, param: &mut Param) {
+        self.visit_vtype(&mut param.ty);
+        self.visit_span(&mut param.span);
+    }
+
+    fn visit_class_def(&mut self, class: &mut ClassDef) {
+        self.walk_class_def(class)
+    }
+
+    fn walk_class_def(&mut self, class: &mut ClassDef) {
+        for member in class.members.iter_mut() {
+            self.visit_class_member(member);
+        }
+        for decl in class.consts.iter_mut() {
+            self.visit_const_decl(decl);
+        }
+        self.visit_span(&mut class.span);
+    }
+
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        self.walk_class_member(member)
+    }
+
+    fn walk_class_member(&mut self, member: &mut ClassMember) {
+        self.visit_vtype(&mut member.ty);
+        self.visit_span(&mut member.span);
+    }
+
+    fn visit_new_class(&mut self, class: &mut NewClass) {
+        self.walk_new_class(class)
+    }
+
+    fn walk_new_class(&mut self, class: &mut NewClass) {
+        for member in class.members.iter_mut() {
+            self.visit_new_class_member(member);
+        }
+        self.visit_span(&mut class.span);
+    }
+
+    fn visit_new_class_member(&mut self, member: &mut NewClassMember) {
+        self.walk_new_class_member(member)
+    }
+
+    fn walk_new_class_member(&mut self, member: &mut NewClassMember) {
+        self.visit_exp(&mut member.val);
+        self.visit_span(&mut member.span);
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        self.walk_block(block)
+    }
+
+    fn walk_block(&mut self, block: &mut Block) {
+        for item in block.items.iter_mut() {
+            self.visit_block_item(item);
+        }
+        self.visit_span(&mut block.span);
+    }
+
+    fn visit_block_item(&mut self, item: &mut BlockItem) {
+        self.walk_block_item(item)
+    }
+
+    fn walk_block_item(&mut self, item: &mut BlockItem) {
+        match item {
+            BlockItem::Decl(decl) => self.visit_decl(decl),
+            BlockItem::Stmt(stmt) => self.visit_stmt(stmt),
+        }
+    }
+
+    fn visit_decl(&mut self, decl: &mut Decl) {
+        self.walk_decl(decl)
+    }
+
+    fn walk_decl(&mut self, decl: &mut Decl) {
+        match decl {
+            Decl::Const(decl) => self.visit_const_decl(decl),
+            Decl::Var(decl) => self.visit_var_decl(decl),
+        }
+    }
+
+    fn visit_var_decl(&mut self, decl: &mut VarDecl) {
+        self.walk_var_decl(decl)
+    }
+
+    fn walk_var_decl(&mut self, decl: &mut VarDecl) {
+        self.visit_vtype(&mut decl.ty);
+        self.visit_init_val(&mut decl.init);
+        self.visit_span(&mut decl.span);
+    }
+
+    fn visit_init_val(&mut self, init: &mut InitVal) {
+        self.walk_init_val(init)
+    }
+
+    fn walk_init_val(&mut self, init: &mut InitVal) {
+        self.visit_exp(&mut init.exp);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        self.walk_stmt(stmt)
+    }
+
+    fn walk_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Assign(assign) => self.visit_assign(assign),
+            Stmt::Return(ret) => self.visit_return(ret),
+            Stmt::Block(block) => self.visit_block(block),
+            Stmt::Exp(exp) => {
+                if let Some(exp) = exp {
+                    self.visit_exp(exp);
+                }
+            }
+            Stmt::If(if_stmt) => self.visit_if(if_stmt),
+            Stmt::InlineAsm(asm) => self.visit_inline_asm(asm),
+            Stmt::While(while_stmt) => self.visit_while(while_stmt),
+            Stmt::Terminator(terminator) => self.visit_terminator(terminator),
+            Stmt::For(for_stmt) => self.visit_for(for_stmt),
+        }
+    }
+
+    fn visit_assign(&mut self, assign: &mut Assign) {
+        self.walk_assign(assign)
+    }
+
+    fn walk_assign(&mut self, assign: &mut Assign) {
+        match assign {
+            Assign::WriteVar(lval, exp, span) => {
+                self.visit_lval(lval);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Assign::WritePtr(deref, exp, span) => {
+                self.visit_deref(deref);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_return(&mut self, ret: &mut Return) {
+        self.walk_return(ret)
+    }
+
+    fn walk_return(&mut self, ret: &mut Return) {
+        if let Some(exp) = &mut ret.exp {
+            self.visit_exp(exp);
+        }
+        self.visit_span(&mut ret.span);
+    }
+
+    fn visit_if(&mut self, if_stmt: &mut If) {
+        self.walk_if(if_stmt)
+    }
+
+    fn walk_if(&mut self, if_stmt: &mut If) {
+        self.visit_exp(&mut if_stmt.cond);
+        self.visit_block(&mut if_stmt.then);
+        if let Some(else_then) = &mut if_stmt.else_then {
+            self.visit_block(else_then);
+        }
+        self.visit_span(&mut if_stmt.span);
+    }
+
+    fn visit_while(&mut self, while_stmt: &mut While) {
+        self.walk_while(while_stmt)
+    }
+
+    fn walk_while(&mut self, while_stmt: &mut While) {
+        self.visit_exp(&mut while_stmt.cond);
+        self.visit_block(&mut while_stmt.then);
+        self.visit_span(&mut while_stmt.span);
+    }
+
+    fn visit_for(&mut self, for_stmt: &mut For) {
+        self.walk_for(for_stmt)
+    }
+
+    fn walk_for(&mut self, for_stmt: &mut For) {
+        self.visit_exp(&mut for_stmt.start);
+        self.visit_exp(&mut for_stmt.end);
+        self.visit_exp(&mut for_stmt.step);
+        self.visit_block(&mut for_stmt.then);
+        self.visit_span(&mut for_stmt.span);
+    }
+
+    fn visit_inline_asm(&mut self, asm: &mut InlineAsm) {
+        self.walk_inline_asm(asm)
+    }
+
+    fn walk_inline_asm(&mut self, asm: &mut InlineAsm) {
+        for constraint in asm.constraints.iter_mut() {
+            self.visit_asm_constraint(constraint);
+        }
+        self.visit_span(&mut asm.span);
+    }
+
+    fn visit_asm_constraint(&mut self, constraint: &mut AsmConstraint) {
+        self.walk_asm_constraint(constraint)
+    }
+
+    fn walk_asm_constraint(&mut self, constraint: &mut AsmConstraint) {
+        match constraint {
+            AsmConstraint::In(_, exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            AsmConstraint::Out(_, lval, span) => {
+                self.visit_lval(lval);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &mut Terminator) {
+        self.walk_terminator(terminator)
+    }
+
+    fn walk_terminator(&mut self, terminator: &mut Terminator) {
+        match terminator {
+            Terminator::Break(span) | Terminator::Continue(span) => self.visit_span(span),
+        }
+    }
+
+    fn visit_builtin_function_call(&mut self, call: &mut BuiltinFunctionCall) {
+        self.walk_builtin_function_call(call)
+    }
+
+    fn walk_builtin_function_call(&mut self, call: &mut BuiltinFunctionCall) {
+        for arg in call.args.iter_mut() {
+            self.visit_exp(arg);
+        }
+        self.visit_span(&mut call.span);
+    }
+
+    fn visit_exp(&mut self, exp: &mut Exp) {
+        self.walk_exp(exp)
+    }
+
+    fn walk_exp(&mut self, exp: &mut Exp) {
+        match exp {
+            Exp::Exp(exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Exp::Number(number) => self.visit_number(number),
+            Exp::LVal(lval) => self.visit_lval(lval),
+            Exp::FuncCall(call) => self.visit_func_call(call),
+            Exp::Str(_, span) => self.visit_span(span),
+            Exp::Unary(op, exp, span) => {
+                self.visit_unary_op(op);
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Exp::Binary(lhs, op, rhs, span) => {
+                self.visit_exp(lhs);
+                self.visit_binary_op(op);
+                self.visit_exp(rhs);
+                self.visit_span(span);
+            }
+            Exp::GetAddr(get_addr) => self.visit_get_addr(get_addr),
+            Exp::Deref(deref) => self.visit_deref(deref),
+            Exp::Array(array) => self.visit_array(array),
+            Exp::Float(float) => self.visit_float(float),
+        }
+    }
+
+    fn visit_number(&mut self, number: &mut Number) {
+        self.walk_number(number)
+    }
+
+    fn walk_number(&mut self, number: &mut Number) {
+        self.visit_span(&mut number.span);
+    }
+
+    fn visit_float(&mut self, float: &mut Float) {
+        self.walk_float(float)
+    }
+
+    fn walk_float(&mut self, float: &mut Float) {
+        self.visit_span(&mut float.span);
+    }
+
+    fn visit_lval(&mut self, lval: &mut LVal) {
+        self.walk_lval(lval)
+    }
+
+    fn walk_lval(&mut self, lval: &mut LVal) {
+        if let Some(exp) = &mut lval.exp {
+            self.visit_exp(exp);
+        }
+        self.visit_span(&mut lval.span);
+    }
+
+    fn visit_func_call(&mut self, call: &mut FuncCall) {
+        self.walk_func_call(call)
+    }
+
+    fn walk_func_call(&mut self, call: &mut FuncCall) {
+        for arg in call.args.iter_mut() {
+            self.visit_exp(arg);
+        }
+        self.visit_span(&mut call.span);
+    }
+
+    fn visit_get_addr(&mut self, get_addr: &mut GetAddr) {
+        self.walk_get_addr(get_addr)
+    }
+
+    fn walk_get_addr(&mut self, get_addr: &mut GetAddr) {
+        self.visit_lval(&mut get_addr.lval);
+        self.visit_span(&mut get_addr.span);
+    }
+
+    fn visit_deref(&mut self, deref: &mut Deref) {
+        self.walk_deref(deref)
+    }
+
+    fn walk_deref(&mut self, deref: &mut Deref) {
+        match deref {
+            Deref::DerefId(lval, span) => {
+                self.visit_lval(lval);
+                self.visit_span(span);
+            }
+            Deref::DerefExp(exp, span) => {
+                self.visit_exp(exp);
+                self.visit_span(span);
+            }
+            Deref::DerefPtrExp(exp, index, span) => {
+                self.visit_exp(exp);
+                self.visit_exp(index);
+                self.visit_span(span);
+            }
+            Deref::DerefPtr(lval, index, span) => {
+                self.visit_lval(lval);
+                self.visit_exp(index);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_array(&mut self, array: &mut Array) {
+        self.walk_array(array)
+    }
+
+    fn walk_array(&mut self, array: &mut Array) {
+        match array {
+            Array::List(values, span) => {
+                for value in values.iter_mut() {
+                    self.visit_exp(value);
+                }
+                self.visit_span(span);
+            }
+            Array::Template(value, count, span) => {
+                self.visit_exp(value);
+                self.visit_const_exp(count);
+                self.visit_span(span);
+            }
+        }
+    }
+
+    fn visit_vtype(&mut self, vtype: &mut VType) {
+        self.walk_vtype(vtype)
+    }
+
+    fn walk_vtype(&mut self, vtype: &mut VType) {
+        self.visit_span(&mut vtype.span);
+    }
+
+    fn visit_unary_op(&mut self, _op: &mut UnaryOp) {}
+
+    fn visit_binary_op(&mut self, _op: &mut BinaryOp) {}
+
+    fn visit_span(&mut self, _span: &mut Span) {}
+}
@@ -0,0 +1,103 @@
+use rasta::build::*;
+use rasta::*;
+
+fn load(deref: Deref) -> Exp {
+    Exp::Deref(Box::new(deref))
+}
+
+/// A program using most builders, which resolves and checks
+fn sample() -> CompUnit {
+    let putc = proto("putc", void()).param("c", i8());
+    let main = func("main", u64())
+        .attr("top")
+        .param("p", ptr(u64()))
+        .param("n", u64())
+        .var("xs", ptr(u64()), array([num(1), hex(2), bin(3)]))
+        .var("ys", ptr(u64()), array_repeat(num(0), num(4)))
+        .var("f", f64(), neg(float(1.5)))
+        .item(constant("K", suffixed(7, u8())))
+        .item(for_(
+            "i",
+            num(0),
+            var("n"),
+            num(1),
+            block([if_else(
+                gt(var("i"), num(1)),
+                block([break_().into()]),
+                block([continue_().into()]),
+            )
+            .into()]),
+        ))
+        .item(while_(
+            ne(load(deref("p")), num(0)),
+            block([store(deref("p"), sub(load(deref("p")), num(1))).into()]),
+        ))
+        .item(store(
+            index("p", num(1)),
+            mul(paren(add(num(1), num(2))), num(3)),
+        ))
+        .item(assign("p", addr_of("n")))
+        .item(exp_stmt(call("putc", [suffixed(97, i8())])))
+        .item(asm_out(asm("ld", [("a", bit_not(var("n")))]), "b", "n"))
+        .ret(shl(var("n"), num(1)));
+    unit([putc.into(), main.into()])
+}
+
+const PRINTED: &str = "\
+const putc = fn (arg c: i8) -> void;
+
+#[top]
+const main = fn (arg p: u64*, arg n: u64) -> u64 {
+    var xs: u64* = [1, 0x2, 0b11];
+    var ys: u64* = [0; 4];
+    var f: f64 = -1.5;
+    const K = 7u8;
+    for i in (0, n, 1) {
+        if i > 1 {
+            break;
+        } else {
+            continue;
+        }
+    }
+    while *p != 0 {
+        *p = *p - 1;
+    }
+    p[1] = (1 + 2) * 3;
+    p = &n;
+    putc(97i8);
+    asm(\"ld\", in a = ~n, out b = n);
+    return n << 1;
+};
+";
+
+#[test]
+fn builds_what_it_prints() {
+    assert_eq!(unparse(&sample()), PRINTED);
+}
+
+#[test]
+fn builds_programs_that_check() {
+    let unit = sample();
+    let ids = NodeIds::new(&unit);
+    let resolution = resolve(&unit, &ids);
+    let types = type_check(&unit, &ids, &resolution);
+    assert!(resolution.is_ok(), "{:?}", resolution.diagnostics);
+    assert!(types.is_ok(), "{:?}", types.diagnostics);
+}
+
+#[test]
+fn every_span_is_dummy() {
+    struct Dummies(usize);
+    impl Visitor for Dummies {
+        fn visit_span(&mut self, span: &Span) {
+            assert!(span.is_dummy(), "{:?}", span);
+            self.0 += 1;
+        }
+    }
+
+    let unit = sample();
+    assert_eq!(unit.source_map.files().count(), 0);
+    let mut dummies = Dummies(0);
+    dummies.visit_comp_unit(&unit);
+    assert!(dummies.0 > 0);
+}
@@ -0,0 +1,204 @@
+use rasta::{Diagnostic, DiagnosticFormat, FileId, SourceMap, Span};
+
+const SOURCE: &str = "const a = 1;
+const b = fn () -> u8 {
+  return 1;
+  x;
+  y;
+  z;
+};
+\tconst c = 2;
+const 名前 = 3;
+";
+
+fn map() -> (SourceMap, FileId) {
+    let mut map = SourceMap::new();
+    let file = map.add_file("test.cara".to_string(), SOURCE.to_string());
+    (map, file)
+}
+
+/// The span of the first `text` in the source, `len` bytes long
+fn span(file: FileId, text: &str, len: usize) -> Span {
+    let start = SOURCE.find(text).unwrap();
+    Span::new(file, start..start + len)
+}
+
+#[test]
+fn labels_notes_and_help() {
+    let (map, file) = map();
+    let diagnostic = Diagnostic::error("mismatched types")
+        .with_code("E1")
+        .with_secondary(span(file, "const a", 7), "defined here")
+        .with_primary(span(file, "1;", 1), "expected `u8`")
+        .with_note("a note")
+        .with_help("a help");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "\
+error[E1]: mismatched types
+ --> test.cara:1:11
+  |
+1 | const a = 1;
+  |           ^ expected `u8`
+  | ------- defined here
+  = note: a note
+  = help: a help
+"
+    );
+}
+
+#[test]
+fn long_spans_show_their_first_and_last_two_lines() {
+    let (map, file) = map();
+    let start = SOURCE.find("const b").unwrap();
+    let end = SOURCE.find("};").unwrap() + 2;
+    let diagnostic =
+        Diagnostic::error("long").with_primary(Span::new(file, start..end), "this function");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "\
+error: long
+ --> test.cara:2:1
+  |
+2 | const b = fn () -> u8 {
+  | ^^^^^^^^^^^^^^^^^^^^^^^
+3 |   return 1;
+  |   ^^^^^^^^^
+...
+6 |   z;
+  |   ^^
+7 | };
+  | ^^ this function
+"
+    );
+}
+
+#[test]
+fn short_spans_show_every_line() {
+    let (map, file) = map();
+    let start = SOURCE.find("x;").unwrap();
+    let end = SOURCE.find("z;").unwrap() + 2;
+    let diagnostic = Diagnostic::error("short").with_primary(Span::new(file, start..end), "");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "\
+error: short
+ --> test.cara:4:3
+  |
+4 |   x;
+  |   ^^
+5 |   y;
+  |   ^^
+6 |   z;
+  |   ^^
+"
+    );
+}
+
+#[test]
+fn tabs_are_expanded() {
+    let (map, file) = map();
+    let diagnostic = Diagnostic::error("tab").with_primary(span(file, "c =", 1), "here");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "\
+error: tab
+ --> test.cara:8:8
+  |
+8 |     const c = 2;
+  |           ^ here
+"
+    );
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    let (map, file) = map();
+    let diagnostic = Diagnostic::error("wide")
+        .with_primary(span(file, "3;", 1), "here")
+        .with_secondary(span(file, "名前", "名前".len()), "name");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "\
+error: wide
+ --> test.cara:9:12
+  |
+9 | const 名前 = 3;
+  |              ^ here
+  |       ---- name
+"
+    );
+}
+
+#[test]
+fn dummy_spans_have_no_snippet() {
+    let (map, _) = map();
+    let diagnostic = Diagnostic::error("generated").with_primary(Span::dummy(), "here");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Plain),
+        "error: generated\n--> <generated>\n"
+    );
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Colored),
+        "\x1b[1;31merror\x1b[0m\x1b[1m: generated\x1b[0m\n\x1b[1;34m-->\x1b[0m <generated>\n"
+    );
+}
+
+#[test]
+fn colored_snippet() {
+    let (map, file) = map();
+    let diagnostic = Diagnostic::warning("unused").with_primary(span(file, "a", 1), "");
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Colored),
+        "\
+\x1b[1;33mwarning\x1b[0m\x1b[1m: unused\x1b[0m
+ \x1b[1;34m-->\x1b[0m test.cara:1:7
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m const a = 1;
+  \x1b[1;34m|\x1b[0m       \x1b[1;33m^\x1b[0m
+"
+    );
+}
+
+#[test]
+fn json() {
+    let (map, file) = map();
+    let diagnostic = Diagnostic::warning("json")
+        .with_code("W1")
+        .with_primary(span(file, "名前", "名前".len()), "here")
+        .with_secondary(Span::dummy(), "generated")
+        .with_note("a note");
+    let start = SOURCE.find("名前").unwrap();
+    assert_eq!(
+        diagnostic.to_json(&map),
+        serde_json::json!({
+            "severity": "warning",
+            "code": "W1",
+            "message": "json",
+            "labels": [
+                {
+                    "primary": true,
+                    "message": "here",
+                    "file": "test.cara",
+                    "start": { "line": 9, "column": 7 },
+                    "end": { "line": 9, "column": 9 },
+                    "bytes": [start, start + "名前".len()],
+                },
+                {
+                    "primary": false,
+                    "message": "generated",
+                    "file": null,
+                    "start": null,
+                    "end": null,
+                    "bytes": [0, 0],
+                },
+            ],
+            "notes": ["a note"],
+            "help": [],
+        })
+    );
+    assert_eq!(
+        diagnostic.render(&map, DiagnosticFormat::Json),
+        diagnostic.to_json(&map).to_string()
+    );
+}
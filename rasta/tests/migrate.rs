@@ -0,0 +1,116 @@
+//! The fixtures are `fixtures/machine.cara` serialized by the rasta of each schema version
+//! that needs a migration, with its `-b` turned into the `Not` the parser couldn't write.
+
+use rasta::*;
+
+const V0: &[u8] = include_bytes!("fixtures/machine-v0.rast");
+const V3: &[u8] = include_bytes!("fixtures/machine-v3.rast");
+const V6: &[u8] = include_bytes!("fixtures/machine-v6.rast");
+const V7: &[u8] = include_bytes!("fixtures/machine-v7.rast");
+
+const SOURCE: &str = include_str!("fixtures/machine.cara");
+
+const PRINTED: &str = "\
+#[top]
+const Machine = fn (arg a: u64, arg b: u64) -> u64 {
+    var c: u64 = 7;
+    if a == 1 {
+        return ~b;
+    }
+    return a * b + c;
+};
+";
+
+/// The source rebuilt from the lines the spans of the fixture start on
+const REBUILT: &str = "\
+#[top]
+const Machine = fn (arg a: u64, arg b: u64) -> u64 {
+    var c: u64 = 7;
+    if a == 1 {
+        return -b;
+
+    return a * b + c;";
+
+/// The source text of every number and unary expression
+struct Literals<'a> {
+    map: &'a SourceMap,
+    numbers: Vec<(String, Option<VTypeEnum>, Radix, String)>,
+    unary: Vec<String>,
+}
+
+impl Visitor for Literals<'_> {
+    fn visit_exp(&mut self, exp: &Exp) {
+        let map = self.map;
+        let text = |span: &Span| span.text(map).unwrap().to_string();
+        match exp {
+            Exp::Number(number) => self.numbers.push((
+                number.num.to_string(),
+                number.suffix.clone(),
+                number.radix,
+                text(&number.span),
+            )),
+            Exp::Unary(UnaryOp::BitNot, ..) => self.unary.push(text(&exp.full_span())),
+            Exp::Unary(..) => panic!("the old `Not` should be a `BitNot`"),
+            _ => {}
+        }
+        self.walk_exp(exp)
+    }
+}
+
+/// Load a fixture, checking it validates and migrates to the current schema
+fn load(data: &[u8], version: u32, source: &str) -> CompUnit {
+    validate_msgpack(data).unwrap();
+    let (header, unit) = deserialize_with_header_from(data).unwrap();
+    assert_eq!(header.schema_version, version);
+    assert_eq!(unparse(&unit), PRINTED);
+
+    let files: Vec<_> = unit
+        .source_map
+        .files()
+        .map(|(_, file)| (file.name(), file.src()))
+        .collect();
+    assert_eq!(files, [("machine.cara", source)]);
+
+    let mut literals = Literals {
+        map: &unit.source_map,
+        numbers: Vec::new(),
+        unary: Vec::new(),
+    };
+    literals.visit_comp_unit(&unit);
+    let decimal = |num: &str| (num.to_string(), None, Radix::Decimal, num.to_string());
+    assert_eq!(literals.numbers, [decimal("7"), decimal("1")]);
+    assert_eq!(literals.unary, ["-b"]);
+
+    unit
+}
+
+#[test]
+fn bare_comp_unit() {
+    load(V0, 0, REBUILT);
+}
+
+#[test]
+fn spans_with_file_names() {
+    let unit = load(V3, 3, REBUILT);
+    assert_eq!(to_json(&unit).unwrap(), to_json(&load(V0, 0, REBUILT)).unwrap());
+}
+
+#[test]
+fn fixed_width_numbers() {
+    load(V6, 6, SOURCE);
+}
+
+#[test]
+fn bitwise_not() {
+    let unit = load(V7, 7, SOURCE);
+    assert_eq!(to_json(&unit).unwrap(), to_json(&load(V6, 6, SOURCE)).unwrap());
+}
+
+#[test]
+fn current_version() {
+    let unit = load(V7, 7, SOURCE);
+    let data = serialize(&unit).unwrap();
+    let (header, reloaded) = deserialize_with_header_from(data.as_slice()).unwrap();
+    assert_eq!(header.schema_version, SCHEMA_VERSION);
+    assert_eq!(to_json(&reloaded).unwrap(), to_json(&unit).unwrap());
+}
@@ -0,0 +1,161 @@
+use rasta::build::*;
+use rasta::*;
+
+/// `a + a` and the same statement twice, so siblings only differ by address
+fn sample() -> CompUnit {
+    let f = func("f", u64())
+        .param("a", u64())
+        .var("x", u64(), add(var("a"), var("a")))
+        .item(assign("x", var("a")))
+        .item(assign("x", var("a")))
+        .ret(var("x"));
+    unit([f.into()])
+}
+
+/// The id of every node that gets one, in the order a visitor reaches them
+struct Collect<'a, 'b> {
+    ids: &'b NodeIds<'a>,
+    found: Vec<Option<NodeId>>,
+}
+
+impl Visitor for Collect<'_, '_> {
+    fn visit_exp(&mut self, exp: &Exp) {
+        self.found.push(self.ids.exp(exp));
+        self.walk_exp(exp)
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.found.push(self.ids.stmt(stmt));
+        self.walk_stmt(stmt)
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        self.found.push(self.ids.decl(decl));
+        self.walk_decl(decl)
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        self.found.push(self.ids.const_decl(decl));
+        self.walk_const_decl(decl)
+    }
+
+    fn visit_lval(&mut self, lval: &LVal) {
+        self.found.push(self.ids.lval(lval));
+        self.walk_lval(lval)
+    }
+}
+
+fn collect(unit: &CompUnit, ids: &NodeIds) -> Vec<NodeId> {
+    let mut collect = Collect {
+        ids,
+        found: Vec::new(),
+    };
+    collect.visit_comp_unit(unit);
+    collect.found.into_iter().map(Option::unwrap).collect()
+}
+
+/// The body of the only function of `unit`
+fn body(unit: &CompUnit) -> &Block {
+    let GlobalItem::ConstDecl(decl) = &unit.global_items[0] else {
+        panic!("not a constant");
+    };
+    let ConstInitVal::Function(func) = &decl.init else {
+        panic!("not a function");
+    };
+    &func.block
+}
+
+#[test]
+fn ids_follow_visiting_order() {
+    let unit = sample();
+    let ids = NodeIds::new(&unit);
+    let found = collect(&unit, &ids);
+    assert_eq!(found.len(), ids.len());
+    assert!(found.iter().map(|id| id.index()).eq(0..ids.len()));
+}
+
+#[test]
+fn ids_are_stable() {
+    let unit = sample();
+    let first = collect(&unit, &NodeIds::new(&unit));
+    assert_eq!(collect(&unit, &NodeIds::new(&unit)), first);
+
+    let reloaded = deserialize(serialize(&unit).unwrap()).unwrap();
+    assert_eq!(collect(&reloaded, &NodeIds::new(&reloaded)), first);
+}
+
+#[test]
+fn siblings_get_distinct_ids() {
+    let unit = sample();
+    let ids = NodeIds::new(&unit);
+    let items = &body(&unit).items;
+
+    let BlockItem::Decl(Decl::Var(x)) = &items[0] else {
+        panic!("not a variable");
+    };
+    let Exp::Binary(lhs, _, rhs, _) = &x.init.exp else {
+        panic!("not `a + a`");
+    };
+    let (lhs, rhs) = (ids.exp(lhs).unwrap(), ids.exp(rhs).unwrap());
+    assert_ne!(lhs, rhs);
+    assert!(lhs < rhs);
+
+    let (BlockItem::Stmt(first), BlockItem::Stmt(second)) = (&items[1], &items[2]) else {
+        panic!("not statements");
+    };
+    assert_ne!(ids.stmt(first).unwrap(), ids.stmt(second).unwrap());
+}
+
+#[test]
+fn nodes_of_other_trees_have_no_id() {
+    let unit = sample();
+    let ids = NodeIds::new(&unit);
+    let BlockItem::Stmt(stmt) = &body(&unit).items[3] else {
+        panic!("not a statement");
+    };
+    assert!(ids.stmt(stmt).is_some());
+    assert_eq!(ids.stmt(&ret(var("x"))), None);
+    assert_eq!(ids.exp(&var("x")), None);
+}
+
+#[test]
+fn node_map() {
+    let unit = sample();
+    let ids = collect(&unit, &NodeIds::new(&unit));
+    let (a, b, c) = (ids[1], ids[4], ids[7]);
+
+    let mut map = NodeMap::new();
+    assert!(map.is_empty());
+    assert_eq!(map.get(b), None);
+    assert_eq!(map.insert(b, "b"), None);
+    assert_eq!(map.insert(a, "a"), None);
+    assert_eq!(map.insert(b, "B"), Some("b"));
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(a), Some(&"a"));
+    assert_eq!(map[b], "B");
+    assert!(!map.contains(c));
+    // Ids past the last one stored are just missing
+    assert_eq!(map.get(c), None);
+    assert_eq!(map.remove(c), None);
+
+    *map.get_mut(a).unwrap() = "A";
+    map[b] = "bb";
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(a, &"A"), (b, &"bb")]);
+
+    assert_eq!(map.remove(a), Some("A"));
+    assert_eq!(map.remove(a), None);
+    assert_eq!(map.len(), 1);
+
+    let collected: NodeMap<_> = [(c, 3), (a, 1), (c, 4)].into_iter().collect();
+    assert_eq!(collected.iter().collect::<Vec<_>>(), [(a, &1), (c, &4)]);
+}
+
+#[test]
+#[should_panic(expected = "no value for the node")]
+fn node_map_index_of_a_missing_node() {
+    let unit = sample();
+    let ids = collect(&unit, &NodeIds::new(&unit));
+    let map: NodeMap<u32> = NodeMap::new();
+    let _ = map[ids[0]];
+}
@@ -0,0 +1,140 @@
+use std::io::ErrorKind;
+
+use rasta::build::*;
+use rasta::*;
+
+fn sample() -> CompUnit {
+    let inc = func("inc", u64())
+        .param("a", u64())
+        .ret(add(var("a"), num(1)));
+    unit([inc.into()])
+}
+
+/// The AST behind a version `version` container with a valid header
+fn with_version(version: u32) -> Vec<u8> {
+    let mut data = serialize(&sample()).unwrap();
+    data[4..8].copy_from_slice(&version.to_be_bytes());
+    data
+}
+
+#[test]
+fn header_round_trip() {
+    let mut header = Header::new("test".to_string(), vec!["a.cara".to_string()]);
+    header.schema_version = 0;
+    let mut data = Vec::new();
+    serialize_with_header_to(&header, &sample(), &mut data).unwrap();
+    assert_eq!(data[..4], MAGIC);
+    assert_eq!(data[4..8], SCHEMA_VERSION.to_be_bytes());
+
+    let (read, unit) = deserialize_with_header_from(data.as_slice()).unwrap();
+    assert_eq!(read.schema_version, SCHEMA_VERSION);
+    assert_eq!(read.rasta_version, header.rasta_version);
+    assert_eq!(read.producer, "test");
+    assert_eq!(read.sources, ["a.cara"]);
+    assert_eq!(unparse(&unit), unparse(&sample()));
+}
+
+#[test]
+fn empty_input() {
+    let err = deserialize(Vec::new()).unwrap_err();
+    assert!(matches!(&err, SerializeError::Io(err) if err.kind() == ErrorKind::UnexpectedEof));
+    assert_eq!(err.offset(), None);
+}
+
+#[test]
+fn bad_magic() {
+    let mut data = serialize(&sample()).unwrap();
+    data[..4].copy_from_slice(b"NOPE");
+    let err = deserialize(data).unwrap_err();
+    assert!(matches!(err, SerializeError::BadMagic(magic) if magic == *b"NOPE"));
+    assert_eq!(err.offset(), Some(0));
+    assert_eq!(
+        err.to_string(),
+        "not a rasta AST: expected magic \"RAST\", found \"NOPE\""
+    );
+}
+
+#[test]
+fn unsupported_version() {
+    let err = deserialize(with_version(SCHEMA_VERSION + 1)).unwrap_err();
+    assert!(matches!(
+        err,
+        SerializeError::UnsupportedVersion { found, supported }
+            if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+    ));
+    assert_eq!(err.offset(), Some(4));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "AST schema version {} is not supported, this rasta reads up to version {}",
+            SCHEMA_VERSION + 1,
+            SCHEMA_VERSION
+        )
+    );
+}
+
+#[test]
+fn truncated_version() {
+    let data = serialize(&sample()).unwrap();
+    let err = deserialize(data[..6].to_vec()).unwrap_err();
+    assert!(matches!(&err, SerializeError::Io(err) if err.kind() == ErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn truncated_header() {
+    let data = serialize(&sample()).unwrap();
+    let err = deserialize(data[..10].to_vec()).unwrap_err();
+    assert!(matches!(err, SerializeError::Decode { .. }));
+    assert_eq!(err.offset(), Some(10));
+}
+
+#[test]
+fn truncated_payload() {
+    let data = serialize(&sample()).unwrap();
+    let len = data.len() - 1;
+    let err = deserialize(data[..len].to_vec()).unwrap_err();
+    assert!(matches!(err, SerializeError::Decode { .. }));
+    assert_eq!(err.offset(), Some(len as u64));
+    assert!(err
+        .to_string()
+        .starts_with(&format!("failed to decode AST at byte {}: ", len)));
+}
+
+#[test]
+fn invalid_header() {
+    let mut data = serialize(&sample()).unwrap();
+    // A marker MessagePack never uses, in place of the header
+    data[8] = 0xc1;
+    let err = deserialize(data).unwrap_err();
+    assert!(matches!(err, SerializeError::Decode { .. }));
+    assert_eq!(err.offset(), Some(9));
+}
+
+#[test]
+fn truncated_older_payload() {
+    let data = with_version(SCHEMA_VERSION - 1);
+    let len = data.len() - 1;
+    let err = deserialize(data[..len].to_vec()).unwrap_err();
+    assert!(matches!(err, SerializeError::Decode { .. }));
+    assert_eq!(err.offset(), Some(len as u64));
+}
+
+#[test]
+fn legacy_payload() {
+    // A version 0 `CompUnit` is a bare array, so the bytes read as the magic are payload.
+    // This one ends in the middle of the length of its second element.
+    let err = deserialize(vec![0x92, 0x90, 0xdc, 0x00]).unwrap_err();
+    assert!(matches!(err, SerializeError::Decode { .. }));
+    assert_eq!(err.offset(), Some(4));
+}
+
+#[test]
+fn migration_failure() {
+    // Version 3 payloads must be arrays to receive the source map
+    let mut data = MAGIC.to_vec();
+    data.extend(3u32.to_be_bytes());
+    data.extend([0x93, 0xa0, 0xa0, 0x90, 0xc0]);
+    let err = deserialize(data).unwrap_err();
+    assert!(matches!(err, SerializeError::Migrate { from: 3, .. }));
+    assert_eq!(err.offset(), None);
+}
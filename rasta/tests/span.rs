@@ -0,0 +1,109 @@
+use rasta::{FileId, SourceFile, SourceMap, Span};
+
+fn file(src: &str) -> (SourceMap, FileId) {
+    let mut map = SourceMap::new();
+    let id = map.add_file("test.cara".to_string(), src.to_string());
+    (map, id)
+}
+
+fn lines(file: &SourceFile) -> Vec<&str> {
+    (1..).map_while(|line| file.line(line)).collect()
+}
+
+#[test]
+fn line_boundaries() {
+    let (map, id) = file("ab\ncd\n\nef");
+    let file = map.file(id).unwrap();
+    let cases = [
+        (0, (1, 1)),
+        (2, (1, 3)),
+        (3, (2, 1)),
+        (5, (2, 3)),
+        (6, (3, 1)),
+        (7, (4, 1)),
+        (9, (4, 3)),
+    ];
+    for (offset, line_col) in cases {
+        assert_eq!(file.line_col(offset), Some(line_col), "{}", offset);
+    }
+    assert_eq!(file.line_col(10), None);
+
+    assert_eq!(lines(file), ["ab", "cd", "", "ef"]);
+    let offsets: Vec<_> = (0..=5).map(|line| file.line_offset(line)).collect();
+    assert_eq!(offsets, [None, Some(0), Some(3), Some(6), Some(7), None]);
+    assert_eq!(file.line(0), None);
+}
+
+#[test]
+fn trailing_line_break() {
+    let (map, id) = file("ab\n");
+    let file = map.file(id).unwrap();
+    assert_eq!(file.line_col(3), Some((2, 1)));
+    assert_eq!(lines(file), ["ab", ""]);
+}
+
+#[test]
+fn empty_file() {
+    let (map, id) = file("");
+    let file = map.file(id).unwrap();
+    assert_eq!(file.line_col(0), Some((1, 1)));
+    assert_eq!(file.line_offset(1), Some(0));
+    assert_eq!(lines(file), [""]);
+}
+
+#[test]
+fn crlf_line_breaks() {
+    let (map, id) = file("ab\r\ncd\r\n");
+    let file = map.file(id).unwrap();
+    assert_eq!(file.line_col(2), Some((1, 3)));
+    assert_eq!(file.line_col(3), Some((1, 4)));
+    assert_eq!(file.line_col(4), Some((2, 1)));
+    assert_eq!(file.line_offset(2), Some(4));
+    assert_eq!(lines(file), ["ab", "cd", ""]);
+}
+
+#[test]
+fn multibyte_characters() {
+    let src = "é = 1;\n名前 = 2;";
+    let (map, id) = file(src);
+    let file = map.file(id).unwrap();
+    // Columns count characters, not bytes
+    assert_eq!(file.line_col(src.find('=').unwrap()), Some((1, 3)));
+    assert_eq!(file.line_col(src.rfind('=').unwrap()), Some((2, 4)));
+    assert_eq!(file.line_offset(2), Some("é = 1;\n".len()));
+    assert_eq!(lines(file), ["é = 1;", "名前 = 2;"]);
+    // Offsets inside a character have no position
+    assert_eq!(file.line_col(1), None);
+    assert_eq!(file.line_col(src.find('名').unwrap() + 1), None);
+
+    let span = Span::new(id, src.find('名').unwrap()..src.rfind('=').unwrap() - 1);
+    assert_eq!(span.text(&map), Some("名前"));
+    assert_eq!(span.start(&map), Some((2, 1)));
+    assert_eq!(span.end(&map), Some((2, 3)));
+}
+
+#[test]
+fn add_file_dedups_by_name() {
+    let mut map = SourceMap::new();
+    let a = map.add_file("a.cara".to_string(), "const a = 1;".to_string());
+    let b = map.add_file("b.cara".to_string(), "const b = 2;".to_string());
+    assert_ne!(a, b);
+
+    // The file added first under a name is kept
+    let again = map.add_file("a.cara".to_string(), "changed".to_string());
+    assert_eq!(again, a);
+    assert_eq!(map.file(a).unwrap().src(), "const a = 1;");
+    assert_eq!(map.files().count(), 2);
+    assert_eq!(map.find_file("b.cara"), Some(b));
+    assert_eq!(map.find_file("c.cara"), None);
+}
+
+#[test]
+fn dummy_spans_have_no_position() {
+    let (map, _) = file("const a = 1;");
+    let span = Span::dummy();
+    assert!(span.is_dummy());
+    assert_eq!(map.file(span.file()).map(SourceFile::name), None);
+    assert_eq!(span.start(&map), None);
+    assert_eq!(span.text(&map), None);
+}
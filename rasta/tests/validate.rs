@@ -0,0 +1,30 @@
+use rasta::build::*;
+use rasta::{serialize, to_json, validate_json, validate_msgpack, CompUnit};
+
+fn sample() -> CompUnit {
+    let add = func("add", u64())
+        .param("a", u64())
+        .param("b", u64())
+        .ret(add(var("a"), var("b")));
+    unit([add.into()])
+}
+
+#[test]
+fn json() {
+    let json = to_json(&sample()).unwrap();
+    validate_json(&json).unwrap();
+    assert!(validate_json(&format!("{} 1", json)).is_err());
+}
+
+#[test]
+fn msgpack() {
+    let data = serialize(&sample()).unwrap();
+    validate_msgpack(&data).unwrap();
+
+    let mut trailing = data.clone();
+    trailing.push(0);
+    assert!(validate_msgpack(&trailing).is_err());
+
+    assert!(validate_msgpack(&data[..data.len() - 1]).is_err());
+    assert!(validate_msgpack(&data[4..]).is_err());
+}